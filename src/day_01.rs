@@ -113,7 +113,7 @@
 
 const INPUT: &str = include_str!("../input/day_01");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     let depths = load_depths(INPUT);
 
     let increases = count_increases(&depths);
@@ -81,7 +81,7 @@ use regex::Regex;
 
 const INPUT: &str = include_str!("../input/day_02");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     let instructions = parse_instructions(INPUT);
 
     let (horizontal, depth) = follow_instructions(&instructions);
@@ -129,7 +129,7 @@ use std::cmp::Ordering;
 
 const INPUT: &str = include_str!("../input/day_03");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     let diagnostic_report = load_diagnostic_report(INPUT);
 
     let gamma_rate = calculate_rate(&diagnostic_report, Criteria::MostCommon);
@@ -93,40 +93,477 @@
 ///
 /// Figure out which board will win last. Once it wins, what would its final
 /// score be?
-use std::collections::HashMap;
+use itertools::Itertools;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 const INPUT: &str = include_str!("../input/day_04");
 
-pub fn run() {
-    let (numbers, mut boards) = load_bingo_game(INPUT);
-
-    let mut first_found = false;
-    for number in numbers.iter() {
-        let last_one = boards.len() == 1;
-        boards = boards
-            .into_iter()
-            .filter_map(|mut board| {
-                board.mark(number);
-                if board.bingo() {
-                    if !first_found {
-                        first_found = true;
-                        println!(
-                            "The first board that will win has a final score of: {}",
-                            board.score() * number
-                        );
-                    }
-                    if last_one {
-                        println!(
-                            "The last board that will win has a final score of: {}",
-                            board.score() * number
-                        );
-                    }
-                    None
-                } else {
-                    Some(board)
+pub fn run(options: &[&str]) {
+    let mut step_style = None;
+    let mut solve_for = None;
+    let mut rule_options = vec![];
+    for &option in options {
+        match option.split_once('=').unwrap_or((option, "")) {
+            // step through the game with step, step=plain or step=html
+            ("step", "") => step_style = Some(RenderStyle::Terminal),
+            ("step", "plain") => step_style = Some(RenderStyle::Plain),
+            ("step", "html") => step_style = Some(RenderStyle::Html),
+            ("step", style) => panic!("Unknown render style: {}", style),
+            // find the draws making a board win first with solve=N
+            ("solve", board) => {
+                solve_for = Some(
+                    board
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|board| board.checked_sub(1))
+                        .unwrap_or_else(|| panic!("Invalid board to solve for: {}", board)),
+                )
+            }
+            _ => rule_options.push(option),
+        }
+    }
+    let rules = parse_win_rules(&rule_options);
+
+    let (numbers, boards) = load_bingo_game(INPUT)
+        .unwrap_or_else(|(board, error)| panic!("Bingo board {} is invalid: {}", board + 1, error));
+
+    if let Some(chosen) = solve_for {
+        match shortest_winning_draws(&boards, chosen, &rules) {
+            Some(draws) => println!(
+                "Drawing {} makes board {} win before all others",
+                draws.iter().join(","),
+                chosen + 1
+            ),
+            None => println!("Board {} can't win before all others", chosen + 1),
+        }
+        return;
+    }
+
+    let mut game = BingoGame::new(numbers, boards, rules);
+
+    if let Some(style) = step_style {
+        if style == RenderStyle::Html {
+            println!("<!DOCTYPE html>\n<html>\n<head>\n<style>td.marked {{ font-weight: bold; background: #ccc; }}</style>\n</head>\n<body>");
+        }
+        while let Some(number) = game.draw() {
+            println!("{}", render_step(&game, number, style));
+        }
+        if style == RenderStyle::Html {
+            println!("</body>\n</html>");
+        }
+    }
+
+    let outcome = game.play();
+
+    print_winners("first", outcome.first_winners());
+    print_winners("last", outcome.last_winners());
+    if !outcome.never_won.is_empty() {
+        println!(
+            "There are {} boards that never win",
+            outcome.never_won.len()
+        );
+    }
+}
+
+fn print_winners(which: &str, winners: &[BingoWin]) {
+    match winners {
+        [] => println!("There is no {} board that will win", which),
+        [win] => println!(
+            "The {} board that will win has a final score of: {}",
+            which, win.score
+        ),
+        _ => println!(
+            "The {} {} boards that will win at the same time have final scores of: {}",
+            which,
+            winners.len(),
+            winners.iter().map(|win| win.score).join(", ")
+        ),
+    }
+}
+
+/// A board getting bingo during a game.
+#[derive(Debug, PartialEq)]
+struct BingoWin {
+    /// index of the board in the order the boards were loaded
+    board: usize,
+    /// index of the drawn number that made the board win
+    turn: usize,
+    number: BingoNumber,
+    line: Vec<Coordinates>,
+    score: BingoNumber,
+}
+
+/// The result of playing a full game of bingo.
+#[derive(Debug, PartialEq)]
+struct BingoOutcome {
+    /// winners in finishing order, boards winning on the same draw are
+    /// ordered by their index
+    winners: Vec<BingoWin>,
+    /// indices of the boards that didn't win with any of the drawn numbers
+    never_won: Vec<usize>,
+}
+
+impl BingoOutcome {
+    fn first_winners(&self) -> &[BingoWin] {
+        self.winners_on_turn(self.winners.first().map(|win| win.turn))
+    }
+
+    fn last_winners(&self) -> &[BingoWin] {
+        self.winners_on_turn(self.winners.last().map(|win| win.turn))
+    }
+
+    fn winners_on_turn(&self, turn: Option<usize>) -> &[BingoWin] {
+        match turn {
+            Some(turn) => {
+                let start = self.winners.partition_point(|win| win.turn < turn);
+                let end = self.winners.partition_point(|win| win.turn <= turn);
+                &self.winners[start..end]
+            }
+            None => &[],
+        }
+    }
+}
+
+/// How many boards are rendered next to each other while stepping through.
+const BOARDS_PER_LINE: usize = 5;
+
+/// Render the state of all boards right after a number was drawn.
+fn render_step(game: &BingoGame, number: BingoNumber, style: RenderStyle) -> String {
+    let turn = game.turn - 1;
+    let winners = game
+        .winners
+        .iter()
+        .filter(|win| win.turn == turn)
+        .map(|win| format!("board {} wins with a score of {}", win.board + 1, win.score))
+        .join(", ");
+    let title = if winners.is_empty() {
+        format!("Turn {}: {} is drawn", turn + 1, number)
+    } else {
+        format!("Turn {}: {} is drawn, {}", turn + 1, number, winners)
+    };
+
+    match style {
+        RenderStyle::Html => format!(
+            "<section>\n<h2>{}</h2>\n{}\n</section>",
+            title,
+            game.boards
+                .iter()
+                .map(|board| board.render(style))
+                .join("\n")
+        ),
+        _ => format!(
+            "{}\n\n{}\n",
+            title,
+            game.boards
+                .chunks(BOARDS_PER_LINE)
+                .map(|boards| render_side_by_side(boards, style))
+                .join("\n\n")
+        ),
+    }
+}
+
+/// Render text boards next to each other, like in the puzzle description.
+fn render_side_by_side(boards: &[BingoBoard], style: RenderStyle) -> String {
+    let rendered: Vec<Vec<String>> = boards
+        .iter()
+        .map(|board| board.render(style).lines().map(str::to_string).collect())
+        .collect();
+    let height = rendered.iter().map(Vec::len).max().unwrap_or(0);
+    // the terminal style has invisible escape codes, pad based on the
+    // visible width of the lines
+    let widths: Vec<usize> = rendered
+        .iter()
+        .map(|lines| {
+            lines
+                .iter()
+                .map(|line| strip_escape_codes(line).len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    (0..height)
+        .map(|row| {
+            rendered
+                .iter()
+                .zip(widths.iter())
+                .map(|(lines, &width)| {
+                    let line = lines.get(row).map(String::as_str).unwrap_or("");
+                    let visible = strip_escape_codes(line).len();
+                    format!("{}{}", line, " ".repeat(width.saturating_sub(visible)))
+                })
+                .join("        ")
+                .trim_end()
+                .to_string()
+        })
+        .join("\n")
+}
+
+fn strip_escape_codes(line: &str) -> String {
+    lazy_static! {
+        static ref RE: Regex = Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    }
+    RE.replace_all(line, "").into_owned()
+}
+
+/// Find the shortest sequence of numbers to draw that makes the chosen board
+/// win before all other boards, None when that's impossible.
+///
+/// Any winning sequence has to contain all numbers of one of the winning
+/// patterns of the chosen board. Drawing more numbers can only mark more
+/// numbers on the other boards, so the shortest sequence consists of exactly
+/// the numbers of the smallest pattern that doesn't complete a pattern on
+/// any other board.
+fn shortest_winning_draws(
+    boards: &[BingoBoard],
+    chosen: usize,
+    rules: &[WinCondition],
+) -> Option<Vec<BingoNumber>> {
+    let board = boards.get(chosen)?;
+    let (rows, columns) = board.dimensions();
+
+    rules
+        .iter()
+        .flat_map(|condition| condition.patterns(rows, columns))
+        .filter_map(|pattern| {
+            pattern
+                .iter()
+                .map(|position| board.numbers.get(position).copied())
+                .collect::<Option<Vec<_>>>()
+        })
+        .filter(|draws| {
+            let drawn: HashSet<_> = draws.iter().copied().collect();
+            boards
+                .iter()
+                .enumerate()
+                .all(|(index, other)| index == chosen || !other.wins_with(&drawn, rules))
+        })
+        .min_by_key(Vec::len)
+}
+
+/// Ways for a board to win, a board wins when any of the patterns of any of
+/// the conditions is completely marked.
+#[derive(Debug, Clone, PartialEq)]
+enum WinCondition {
+    Rows,
+    Columns,
+    /// both diagonals running from the top corners
+    Diagonals,
+    FourCorners,
+    /// all numbers of the board
+    Blackout,
+    /// this many consecutive numbers in a row, column or diagonal
+    InARow(u8),
+    /// a custom set of positions
+    Mask(Vec<Coordinates>),
+}
+
+/// Only complete rows and columns win, diagonals don't count.
+const STANDARD_RULES: &[WinCondition] = &[WinCondition::Rows, WinCondition::Columns];
+
+impl WinCondition {
+    fn parse(option: &str) -> Option<WinCondition> {
+        match option.split_once('=') {
+            Some(("in-a-row", n)) => n.parse().ok().map(WinCondition::InARow),
+            Some(("mask", mask)) => parse_mask(mask).map(WinCondition::Mask),
+            Some(_) => None,
+            None => match option {
+                "rows" => Some(WinCondition::Rows),
+                "columns" => Some(WinCondition::Columns),
+                "diagonals" => Some(WinCondition::Diagonals),
+                "corners" => Some(WinCondition::FourCorners),
+                "blackout" => Some(WinCondition::Blackout),
+                _ => None,
+            },
+        }
+    }
+
+    /// All sets of positions satisfying this condition on a board with the
+    /// given amount of rows and columns.
+    fn patterns(&self, rows: u8, columns: u8) -> Vec<Vec<Coordinates>> {
+        match self {
+            WinCondition::Rows => (0..rows)
+                .map(|x| (0..columns).map(|y| (x, y)).collect())
+                .collect(),
+            WinCondition::Columns => (0..columns)
+                .map(|y| (0..rows).map(|x| (x, y)).collect())
+                .collect(),
+            WinCondition::Diagonals => {
+                let length = rows.min(columns);
+                if length == 0 {
+                    return vec![];
                 }
-            })
+                vec![
+                    (0..length).map(|i| (i, i)).collect(),
+                    (0..length).map(|i| (i, columns - 1 - i)).collect(),
+                ]
+            }
+            WinCondition::FourCorners => {
+                if rows == 0 || columns == 0 {
+                    return vec![];
+                }
+                let mut corners = vec![
+                    (0, 0),
+                    (0, columns - 1),
+                    (rows - 1, 0),
+                    (rows - 1, columns - 1),
+                ];
+                corners.sort_unstable();
+                corners.dedup();
+                vec![corners]
+            }
+            WinCondition::Blackout => {
+                vec![(0..rows)
+                    .flat_map(|x| (0..columns).map(move |y| (x, y)))
+                    .collect()]
+            }
+            WinCondition::InARow(n) => {
+                let n = *n;
+                if n == 0 {
+                    return vec![];
+                }
+                // every starting position in every direction that fits
+                let directions: [(i16, i16); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+                (0..rows)
+                    .flat_map(|x| (0..columns).map(move |y| (x, y)))
+                    .flat_map(|(x, y)| {
+                        directions.iter().filter_map(move |(dx, dy)| {
+                            (0..n as i16)
+                                .map(|i| {
+                                    let x = u8::try_from(x as i16 + dx * i).ok()?;
+                                    let y = u8::try_from(y as i16 + dy * i).ok()?;
+                                    (x < rows && y < columns).then_some((x, y))
+                                })
+                                .collect::<Option<Vec<_>>>()
+                        })
+                    })
+                    .collect()
+            }
+            WinCondition::Mask(mask) => vec![mask.clone()],
+        }
+    }
+}
+
+/// Parse the win conditions given on the command line, the standard rules
+/// are used when none are given:
+///
+///   - rows, columns, lines (both rows and columns)
+///   - diagonals, corners, blackout
+///   - in-a-row=N
+///   - mask=10001/01010/00100/01010/10001 (rows separated by '/', a 1 marks a
+///     position that is part of the pattern)
+fn parse_win_rules(options: &[&str]) -> Vec<WinCondition> {
+    let rules: Vec<_> = options
+        .iter()
+        .flat_map(|&option| match option {
+            "lines" => STANDARD_RULES.to_vec(),
+            _ => vec![WinCondition::parse(option)
+                .unwrap_or_else(|| panic!("Unknown win condition: {}", option))],
+        })
+        .collect();
+    if rules.is_empty() {
+        STANDARD_RULES.to_vec()
+    } else {
+        rules
+    }
+}
+
+fn parse_mask(mask: &str) -> Option<Vec<Coordinates>> {
+    let mut positions = vec![];
+    for (x, row) in mask.split('/').enumerate() {
+        for (y, c) in row.chars().enumerate() {
+            match c {
+                '1' => positions.push((u8::try_from(x).ok()?, u8::try_from(y).ok()?)),
+                '0' => {}
+                _ => return None,
+            }
+        }
+    }
+    if positions.is_empty() {
+        None
+    } else {
+        Some(positions)
+    }
+}
+
+struct BingoGame {
+    numbers: Vec<BingoNumber>,
+    boards: Vec<BingoBoard>,
+    rules: Vec<WinCondition>,
+    turn: usize,
+    winners: Vec<BingoWin>,
+}
+
+impl BingoGame {
+    fn new(
+        numbers: Vec<BingoNumber>,
+        boards: Vec<BingoBoard>,
+        rules: Vec<WinCondition>,
+    ) -> BingoGame {
+        BingoGame {
+            numbers,
+            boards,
+            rules,
+            turn: 0,
+            winners: Vec::new(),
+        }
+    }
+
+    fn has_won(&self, board: usize) -> bool {
+        self.winners.iter().any(|win| win.board == board)
+    }
+
+    fn finished(&self) -> bool {
+        self.turn >= self.numbers.len() || self.winners.len() == self.boards.len()
+    }
+
+    /// Draw the next number and mark it on all boards that haven't won yet.
+    ///
+    /// Returns the drawn number, or None when the game is finished.
+    fn draw(&mut self) -> Option<BingoNumber> {
+        if self.finished() {
+            return None;
+        }
+        let turn = self.turn;
+        let number = self.numbers[turn];
+        self.turn += 1;
+
+        for index in 0..self.boards.len() {
+            if self.has_won(index) {
+                continue;
+            }
+            let board = &mut self.boards[index];
+            board.mark(&number);
+            if let Some(line) = board.winning_line(&self.rules) {
+                let score = board.score() * number;
+                self.winners.push(BingoWin {
+                    board: index,
+                    turn,
+                    number,
+                    line,
+                    score,
+                });
+            }
+        }
+
+        Some(number)
+    }
+
+    fn play(mut self) -> BingoOutcome {
+        while self.draw().is_some() {}
+        self.outcome()
+    }
+
+    fn outcome(self) -> BingoOutcome {
+        let never_won = (0..self.boards.len())
+            .filter(|&index| !self.has_won(index))
             .collect();
+        BingoOutcome {
+            winners: self.winners,
+            never_won,
+        }
     }
 }
 
@@ -137,13 +574,20 @@ type Coordinates = (u8, u8);
 struct BingoBoard {
     numbers: HashMap<Coordinates, BingoNumber>,
     marked: HashMap<Coordinates, bool>,
+    rows: u8,
+    columns: u8,
 }
 
 impl BingoBoard {
     fn new(numbers: HashMap<Coordinates, BingoNumber>) -> BingoBoard {
+        let (rows, columns) = numbers.keys().fold((0, 0), |(rows, columns), &(x, y)| {
+            (rows.max(x + 1), columns.max(y + 1))
+        });
         BingoBoard {
             numbers,
             marked: HashMap::new(),
+            rows,
+            columns,
         }
     }
 
@@ -153,80 +597,214 @@ impl BingoBoard {
         }
     }
 
-    fn bingo(&self) -> bool {
-        // check rows
-        let mut row = 0;
-        while self.numbers.get(&(row, 0)).is_some() {
-            if self
-                .numbers
-                .keys()
-                .filter(|(x, _)| x == &row)
-                .all(|pos| *self.marked.get(pos).unwrap_or(&false))
-            {
-                return true;
-            }
-            row += 1;
-        }
+    /// Find the first completely marked pattern of the given win conditions.
+    fn winning_line(&self, rules: &[WinCondition]) -> Option<Vec<Coordinates>> {
+        let (rows, columns) = self.dimensions();
+        rules
+            .iter()
+            .flat_map(|condition| condition.patterns(rows, columns))
+            .find(|pattern| pattern.iter().all(|pos| self.is_marked(pos)))
+    }
 
-        // check columns
-        let mut column = 0;
-        while self.numbers.get(&(0, column)).is_some() {
-            if self
-                .numbers
-                .keys()
-                .filter(|(_, y)| y == &column)
-                .all(|pos| *self.marked.get(pos).unwrap_or(&false))
-            {
-                return true;
-            }
-            column += 1;
-        }
+    /// Would the board win if exactly these numbers were drawn.
+    fn wins_with(&self, drawn: &HashSet<BingoNumber>, rules: &[WinCondition]) -> bool {
+        rules
+            .iter()
+            .flat_map(|condition| condition.patterns(self.rows, self.columns))
+            .any(|pattern| {
+                pattern.iter().all(|position| {
+                    self.numbers
+                        .get(position)
+                        .is_some_and(|n| drawn.contains(n))
+                })
+            })
+    }
+
+    /// The amount of rows and columns on the board.
+    fn dimensions(&self) -> (u8, u8) {
+        (self.rows, self.columns)
+    }
 
-        return false;
+    fn is_marked(&self, position: &Coordinates) -> bool {
+        *self.marked.get(position).unwrap_or(&false)
     }
 
     fn score(&self) -> BingoNumber {
         self.numbers
             .iter()
-            .filter(|(pos, _)| !*self.marked.get(pos).unwrap_or(&false))
+            .filter(|(pos, _)| !self.is_marked(pos))
             .map(|(_, number)| number)
             .sum()
     }
+
+    fn render(&self, style: RenderStyle) -> String {
+        let width = self
+            .numbers
+            .values()
+            .map(|n| n.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let cell = |position: &Coordinates| {
+            let number = self
+                .numbers
+                .get(position)
+                .map(|n| n.to_string())
+                .unwrap_or_default();
+            match (style, self.is_marked(position)) {
+                (RenderStyle::Plain, false) => format!(" {:>width$} ", number, width = width),
+                (RenderStyle::Plain, true) => format!("[{:>width$}]", number, width = width),
+                (RenderStyle::Terminal, false) => format!("{:>width$}", number, width = width),
+                // bold and inverted
+                (RenderStyle::Terminal, true) => {
+                    format!("\x1b[1;7m{:>width$}\x1b[0m", number, width = width)
+                }
+                (RenderStyle::Html, false) => format!("<td>{}</td>", number),
+                (RenderStyle::Html, true) => format!("<td class=\"marked\">{}</td>", number),
+            }
+        };
+
+        let cell = &cell;
+        let rows = (0..self.rows).map(|x| (0..self.columns).map(move |y| cell(&(x, y))));
+        match style {
+            RenderStyle::Plain => rows
+                .map(|mut row| row.join(" ").trim_end().to_string())
+                .join("\n"),
+            RenderStyle::Terminal => rows.map(|mut row| row.join(" ")).join("\n"),
+            RenderStyle::Html => format!(
+                "<table class=\"bingo-board\">\n{}\n</table>",
+                rows.map(|row| format!("<tr>{}</tr>", row.collect::<String>()))
+                    .join("\n")
+            ),
+        }
+    }
 }
 
-fn load_bingo_game(input: &str) -> (Vec<BingoNumber>, Vec<BingoBoard>) {
-    let mut blocks = input.split("\n\n");
+/// Renders the board as plain text, marked numbers are put between brackets.
+impl fmt::Display for BingoBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(RenderStyle::Plain))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenderStyle {
+    /// marked numbers between brackets
+    Plain,
+    /// marked numbers highlighted with ANSI escape codes
+    Terminal,
+    /// a table with the marked cells having the "marked" class
+    Html,
+}
+
+/// Reasons for a bingo board to be rejected while loading.
+#[derive(Debug, PartialEq)]
+enum BoardError {
+    Empty,
+    InvalidNumber(String),
+    /// a row doesn't have the same amount of numbers as the first row
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    DuplicateNumber(BingoNumber),
+    /// more rows or columns than fit in the coordinates
+    TooLarge,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::Empty => write!(f, "the board has no numbers"),
+            BoardError::InvalidNumber(n) => write!(f, "'{}' is not a valid number", n),
+            BoardError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} numbers, expected {}",
+                row + 1,
+                found,
+                expected
+            ),
+            BoardError::DuplicateNumber(n) => write!(f, "{} appears more than once", n),
+            BoardError::TooLarge => write!(f, "the board is too large"),
+        }
+    }
+}
+
+/// Load the drawn numbers and the boards, on failure the index of the first
+/// invalid board is returned with the reason.
+fn load_bingo_game(
+    input: &str,
+) -> Result<(Vec<BingoNumber>, Vec<BingoBoard>), (usize, BoardError)> {
+    let mut blocks = input.split("\n\n").filter(|block| !block.trim().is_empty());
 
     let numbers = blocks
         .next()
-        .unwrap()
+        .unwrap_or("")
         .split(',')
-        .filter_map(|n| n.parse().ok())
+        .filter_map(|n| n.trim().parse().ok())
         .collect();
-    let boards = blocks.map(parse_board).collect();
+    let boards = blocks
+        .enumerate()
+        .map(|(index, block)| parse_board(block).map_err(|error| (index, error)))
+        .collect::<Result<_, _>>()?;
 
-    (numbers, boards)
+    Ok((numbers, boards))
 }
 
-fn parse_board(board: &str) -> BingoBoard {
-    BingoBoard::new(
-        board
-            .lines()
-            .enumerate()
-            .flat_map(|(x, line)| {
-                line.split_whitespace()
-                    .filter_map(|n| n.parse().ok())
-                    .enumerate()
-                    .map(|(y, n)| ((x as u8, y as u8), n))
-                    .collect::<Vec<_>>()
-            })
-            .collect(),
-    )
+/// Parse a board of any rectangular size, all rows need to have the same
+/// amount of numbers and a number can only appear once.
+fn parse_board(board: &str) -> Result<BingoBoard, BoardError> {
+    let rows = board
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split_whitespace()
+                .map(|n| {
+                    n.parse()
+                        .map_err(|_| BoardError::InvalidNumber(n.to_string()))
+                })
+                .collect::<Result<Vec<BingoNumber>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let columns = rows.first().map(Vec::len).unwrap_or(0);
+    if columns == 0 {
+        return Err(BoardError::Empty);
+    }
+    // the amount of rows and columns has to fit in the coordinates as well
+    let (Ok(height), Ok(width)) = (u8::try_from(rows.len()), u8::try_from(columns)) else {
+        return Err(BoardError::TooLarge);
+    };
+
+    let mut numbers = HashMap::new();
+    let mut seen = HashSet::new();
+    for (x, row) in (0..height).zip(&rows) {
+        if row.len() != columns {
+            return Err(BoardError::Ragged {
+                row: x.into(),
+                expected: columns,
+                found: row.len(),
+            });
+        }
+        for (y, &n) in (0..width).zip(row) {
+            if !seen.insert(n) {
+                return Err(BoardError::DuplicateNumber(n));
+            }
+            numbers.insert((x, y), n);
+        }
+    }
+
+    Ok(BingoBoard::new(numbers))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     fn bingo_board_1() -> BingoBoard {
         // 22 13 17 11  0
@@ -377,7 +955,10 @@ mod tests {
         ];
         let expected_boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
 
-        assert_eq!(load_bingo_game(input), (expected_numbers, expected_boards));
+        assert_eq!(
+            load_bingo_game(input),
+            Ok((expected_numbers, expected_boards))
+        );
     }
 
     #[test]
@@ -393,7 +974,7 @@ mod tests {
     }
 
     #[test]
-    fn test_bingo_board_bingo() {
+    fn test_bingo_board_winning_line() {
         let mut bingo_board_1 = bingo_board_1();
         let mut bingo_board_2 = bingo_board_2();
         let mut bingo_board_3 = bingo_board_3();
@@ -401,23 +982,24 @@ mod tests {
         for n in [7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21].iter() {
             // none of these should be bingo
             bingo_board_1.mark(n);
-            assert!(!bingo_board_1.bingo());
+            assert!(bingo_board_1.winning_line(STANDARD_RULES).is_none());
             bingo_board_2.mark(n);
-            assert!(!bingo_board_2.bingo());
+            assert!(bingo_board_2.winning_line(STANDARD_RULES).is_none());
             bingo_board_3.mark(n);
-            assert!(!bingo_board_3.bingo());
+            assert!(bingo_board_3.winning_line(STANDARD_RULES).is_none());
         }
 
         let n = &24;
         bingo_board_1.mark(n);
-        assert!(!bingo_board_1.bingo());
+        assert!(bingo_board_1.winning_line(STANDARD_RULES).is_none());
         bingo_board_2.mark(n);
-        assert!(!bingo_board_2.bingo());
-        // finally bingo
+        assert!(bingo_board_2.winning_line(STANDARD_RULES).is_none());
+        // finally bingo, on the first row
         bingo_board_3.mark(n);
-        println!("{:?}", bingo_board_3.marked);
-        println!("{:?}", bingo_board_3.numbers);
-        assert!(bingo_board_3.bingo());
+        assert_eq!(
+            bingo_board_3.winning_line(STANDARD_RULES),
+            Some(vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)])
+        );
     }
 
     #[test]
@@ -431,4 +1013,446 @@ mod tests {
 
         assert_eq!(bingo_board.score(), 188);
     }
+
+    fn example_numbers() -> Vec<BingoNumber> {
+        vec![
+            7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
+            3, 26, 1,
+        ]
+    }
+
+    #[test]
+    fn test_bingo_game_play() {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+
+        let outcome = BingoGame::new(example_numbers(), boards, STANDARD_RULES.to_vec()).play();
+
+        let winners: Vec<_> = outcome
+            .winners
+            .iter()
+            .map(|win| (win.board, win.turn, win.number, win.score))
+            .collect();
+        assert_eq!(
+            winners,
+            vec![(2, 11, 24, 4512), (0, 13, 16, 2192), (1, 14, 13, 1924)]
+        );
+        assert_eq!(outcome.never_won, Vec::<usize>::new());
+        // the second board wins on its middle column
+        assert_eq!(
+            outcome.winners[2].line,
+            vec![(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn test_bingo_outcome_first_and_last_winners() {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+
+        let outcome = BingoGame::new(example_numbers(), boards, STANDARD_RULES.to_vec()).play();
+
+        assert_eq!(outcome.first_winners().len(), 1);
+        assert_eq!(outcome.first_winners()[0].score, 4512);
+        assert_eq!(outcome.last_winners().len(), 1);
+        assert_eq!(outcome.last_winners()[0].score, 1924);
+    }
+
+    #[test]
+    fn test_bingo_game_never_won() {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+
+        // stop drawing right after the first board has won
+        let numbers = example_numbers().into_iter().take(12).collect();
+        let outcome = BingoGame::new(numbers, boards, STANDARD_RULES.to_vec()).play();
+
+        assert_eq!(outcome.winners.len(), 1);
+        assert_eq!(outcome.never_won, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_bingo_game_simultaneous_winners() {
+        let boards = vec![bingo_board_3(), bingo_board_1(), bingo_board_3()];
+
+        let outcome = BingoGame::new(example_numbers(), boards, STANDARD_RULES.to_vec()).play();
+
+        let first_winners: Vec<_> = outcome
+            .first_winners()
+            .iter()
+            .map(|win| (win.board, win.turn, win.score))
+            .collect();
+        assert_eq!(first_winners, vec![(0, 11, 4512), (2, 11, 4512)]);
+        assert_eq!(outcome.last_winners().len(), 1);
+        assert_eq!(outcome.last_winners()[0].board, 1);
+    }
+
+    #[test]
+    fn test_bingo_outcome_without_winners() {
+        let boards = vec![bingo_board_1()];
+
+        let outcome = BingoGame::new(vec![], boards, STANDARD_RULES.to_vec()).play();
+
+        assert!(outcome.first_winners().is_empty());
+        assert!(outcome.last_winners().is_empty());
+        assert_eq!(outcome.never_won, vec![0]);
+    }
+
+    #[test]
+    fn test_parse_win_rules() {
+        assert_eq!(parse_win_rules(&[]), STANDARD_RULES.to_vec());
+        assert_eq!(
+            parse_win_rules(&["lines", "diagonals"]),
+            vec![
+                WinCondition::Rows,
+                WinCondition::Columns,
+                WinCondition::Diagonals
+            ]
+        );
+        assert_eq!(
+            parse_win_rules(&["corners", "blackout", "in-a-row=4"]),
+            vec![
+                WinCondition::FourCorners,
+                WinCondition::Blackout,
+                WinCondition::InARow(4)
+            ]
+        );
+        assert_eq!(
+            parse_win_rules(&["mask=101/010"]),
+            vec![WinCondition::Mask(vec![(0, 0), (0, 2), (1, 1)])]
+        );
+    }
+
+    #[test]
+    fn test_win_condition_parse_invalid() {
+        assert_eq!(WinCondition::parse("triangles"), None);
+        assert_eq!(WinCondition::parse("in-a-row=many"), None);
+        assert_eq!(WinCondition::parse("mask=1x1"), None);
+        assert_eq!(WinCondition::parse("mask=000"), None);
+        // wider than the coordinates go
+        let wide_mask = format!("mask={}1", "0".repeat(256));
+        assert_eq!(WinCondition::parse(&wide_mask), None);
+    }
+
+    #[test]
+    fn test_win_condition_patterns() {
+        assert_eq!(
+            WinCondition::Diagonals.patterns(3, 3),
+            vec![vec![(0, 0), (1, 1), (2, 2)], vec![(0, 2), (1, 1), (2, 0)]]
+        );
+        assert_eq!(
+            WinCondition::FourCorners.patterns(3, 3),
+            vec![vec![(0, 0), (0, 2), (2, 0), (2, 2)]]
+        );
+        assert_eq!(WinCondition::Blackout.patterns(5, 5)[0].len(), 25);
+        // 3 rows and 3 columns, a single diagonal in both directions
+        assert_eq!(WinCondition::InARow(3).patterns(3, 3).len(), 8);
+        // 5 * 2 per row and column, 2 * 2 per diagonal direction
+        assert_eq!(WinCondition::InARow(4).patterns(5, 5).len(), 28);
+    }
+
+    #[test]
+    fn test_bingo_board_winning_line_diagonal() {
+        let mut bingo_board = bingo_board_1();
+
+        // the diagonal from the top right corner: 0 4 14 10 1
+        for n in [0, 4, 14, 10].iter() {
+            bingo_board.mark(n);
+        }
+        assert_eq!(bingo_board.winning_line(&[WinCondition::Diagonals]), None);
+
+        bingo_board.mark(&1);
+        assert_eq!(bingo_board.winning_line(STANDARD_RULES), None);
+        assert_eq!(
+            bingo_board.winning_line(&[WinCondition::Diagonals]),
+            Some(vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)])
+        );
+    }
+
+    #[test]
+    fn test_bingo_board_winning_line_mask() {
+        let mut bingo_board = bingo_board_1();
+        let rules = [WinCondition::parse("mask=1/01").unwrap()];
+
+        bingo_board.mark(&22);
+        assert_eq!(bingo_board.winning_line(&rules), None);
+
+        bingo_board.mark(&2);
+        assert_eq!(bingo_board.winning_line(&rules), Some(vec![(0, 0), (1, 1)]));
+        // scores are still the sum of the unmarked numbers
+        assert_eq!(bingo_board.score(), 300 - 22 - 2);
+    }
+
+    #[test]
+    fn test_bingo_game_blackout() {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+
+        let outcome =
+            BingoGame::new(example_numbers(), boards, vec![WinCondition::Blackout]).play();
+
+        // every number of the boards is drawn eventually, all boards only win
+        // when the last of their numbers is drawn
+        let winners: Vec<_> = outcome
+            .winners
+            .iter()
+            .map(|win| (win.board, win.number, win.score))
+            .collect();
+        assert_eq!(winners, vec![(1, 3, 0), (2, 26, 0), (0, 1, 0)]);
+    }
+
+    /// A board with the numbers 0 up to rows * columns, row by row.
+    fn numbered_board(rows: usize, columns: usize) -> String {
+        (0..rows)
+            .map(|x| {
+                (0..columns)
+                    .map(|y| format!("{:3}", x * columns + y))
+                    .join(" ")
+            })
+            .join("\n")
+    }
+
+    #[test_case(3, 3 ; "3x3")]
+    #[test_case(4, 4 ; "4x4")]
+    #[test_case(5, 5 ; "5x5")]
+    #[test_case(6, 6 ; "6x6")]
+    #[test_case(7, 7 ; "7x7")]
+    #[test_case(8, 8 ; "8x8")]
+    #[test_case(9, 9 ; "9x9")]
+    #[test_case(10, 10 ; "10x10")]
+    #[test_case(3, 7 ; "3x7")]
+    #[test_case(10, 4 ; "10x4")]
+    fn test_parse_board_sizes(rows: usize, columns: usize) {
+        let board = parse_board(&numbered_board(rows, columns)).unwrap();
+
+        assert_eq!(board.dimensions(), (rows as u8, columns as u8));
+        assert_eq!(board.numbers.len(), rows * columns);
+        assert_eq!(
+            board.numbers.get(&((rows - 1) as u8, (columns - 1) as u8)),
+            Some(&((rows * columns - 1) as BingoNumber))
+        );
+    }
+
+    #[test_case(3, 3 ; "3x3")]
+    #[test_case(5, 5 ; "5x5")]
+    #[test_case(10, 10 ; "10x10")]
+    #[test_case(3, 7 ; "3x7")]
+    #[test_case(10, 4 ; "10x4")]
+    fn test_bingo_game_board_sizes(rows: usize, columns: usize) {
+        let input = format!(
+            "{}\n\n{}\n",
+            // the last column, followed by the whole board
+            (0..rows * columns)
+                .map(|n| (n % rows) * columns + columns - 1 - n / rows)
+                .join(","),
+            numbered_board(rows, columns)
+        );
+        let (numbers, boards) = load_bingo_game(&input).unwrap();
+
+        let outcome = BingoGame::new(numbers, boards, STANDARD_RULES.to_vec()).play();
+
+        let last_column: Vec<_> = (0..rows as u8).map(|x| (x, columns as u8 - 1)).collect();
+        assert_eq!(outcome.winners[0].turn, rows - 1);
+        assert_eq!(outcome.winners[0].line, last_column);
+    }
+
+    #[test]
+    fn test_parse_board_ragged() {
+        let board = "1 2 3\n4 5\n6 7 8";
+
+        assert_eq!(
+            parse_board(board),
+            Err(BoardError::Ragged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_board_duplicate_number() {
+        let board = "1 2 3\n4 5 6\n7 2 9";
+
+        assert_eq!(parse_board(board), Err(BoardError::DuplicateNumber(2)));
+    }
+
+    #[test]
+    fn test_parse_board_too_large() {
+        let wide_row = (0..256)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let tall_board = (0..256)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let largest_row = (0..255)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        assert_eq!(parse_board(&wide_row), Err(BoardError::TooLarge));
+        assert_eq!(parse_board(&tall_board), Err(BoardError::TooLarge));
+        assert_eq!(
+            parse_board(&largest_row).map(|board| board.dimensions()),
+            Ok((1, 255))
+        );
+    }
+
+    #[test]
+    fn test_parse_board_invalid_number() {
+        let board = "1 2 3\n4 x 6\n7 8 9";
+
+        assert_eq!(
+            parse_board(board),
+            Err(BoardError::InvalidNumber("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_load_bingo_game_invalid_board() {
+        let input = "1,2,3\n\n1 2\n3 4\n\n1 2\n3\n";
+
+        assert_eq!(
+            load_bingo_game(input),
+            Err((
+                1,
+                BoardError::Ragged {
+                    row: 1,
+                    expected: 2,
+                    found: 1
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bingo_board_display() {
+        let mut bingo_board = parse_board("1 2 3\n4 5 6\n7 8 10").unwrap();
+        bingo_board.mark(&5);
+        bingo_board.mark(&10);
+
+        assert_eq!(
+            bingo_board.to_string(),
+            ["  1    2    3", "  4  [ 5]   6", "  7    8  [10]"].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_bingo_board_render_terminal() {
+        let mut bingo_board = parse_board("1 2\n3 4").unwrap();
+        bingo_board.mark(&2);
+
+        assert_eq!(
+            bingo_board.render(RenderStyle::Terminal),
+            "1 \x1b[1;7m2\x1b[0m\n3 4"
+        );
+    }
+
+    #[test]
+    fn test_bingo_board_render_html() {
+        let mut bingo_board = parse_board("1 2\n3 4").unwrap();
+        bingo_board.mark(&3);
+
+        assert_eq!(
+            bingo_board.render(RenderStyle::Html),
+            "<table class=\"bingo-board\">\n\
+             <tr><td>1</td><td>2</td></tr>\n\
+             <tr><td class=\"marked\">3</td><td>4</td></tr>\n\
+             </table>"
+        );
+    }
+
+    #[test]
+    fn test_render_side_by_side() {
+        let mut boards = vec![
+            parse_board("1 2\n3 4").unwrap(),
+            parse_board("10 20\n30 40").unwrap(),
+        ];
+        boards[0].mark(&4);
+        boards[1].mark(&4);
+
+        assert_eq!(
+            render_side_by_side(&boards, RenderStyle::Terminal),
+            "1 2        10 20\n3 \x1b[1;7m4\x1b[0m        30 40"
+        );
+    }
+
+    #[test]
+    fn test_render_step() {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+        let mut game = BingoGame::new(example_numbers(), boards, STANDARD_RULES.to_vec());
+
+        for _ in 0..11 {
+            game.draw();
+        }
+        let number = game.draw().unwrap();
+
+        let expected = [
+            "Turn 12: 24 is drawn, board 3 wins with a score of 4512",
+            "",
+            " 22   13  [17] [11] [ 0]          3   15  [ 0] [ 2]  22         [14] [21] [17] [24] [ 4]",
+            "  8  [ 2] [23] [ 4] [24]        [ 9]  18   13  [17] [ 5]         10   16   15  [ 9]  19",
+            "[21] [ 9] [14]  16  [ 7]         19    8  [ 7]  25  [23]         18    8  [23]  26   20",
+            "  6   10    3   18  [ 5]         20  [11]  10  [24] [ 4]         22  [11]  13    6  [ 5]",
+            "  1   12   20   15   19         [14] [21]  16   12    6         [ 2] [ 0]  12    3  [ 7]",
+            "",
+        ];
+        assert_eq!(
+            render_step(&game, number, RenderStyle::Plain),
+            expected.join("\n")
+        );
+    }
+
+    #[test_case(0 ; "first board")]
+    #[test_case(1 ; "second board")]
+    #[test_case(2 ; "third board")]
+    fn test_shortest_winning_draws(chosen: usize) {
+        let boards = vec![bingo_board_1(), bingo_board_2(), bingo_board_3()];
+
+        let draws = shortest_winning_draws(&boards, chosen, STANDARD_RULES).unwrap();
+        assert_eq!(draws.len(), 5);
+
+        let outcome = BingoGame::new(draws, boards, STANDARD_RULES.to_vec()).play();
+        assert_eq!(outcome.winners.len(), 1);
+        assert_eq!(outcome.winners[0].board, chosen);
+        assert_eq!(outcome.winners[0].turn, 4);
+    }
+
+    #[test]
+    fn test_shortest_winning_draws_smallest_pattern() {
+        let boards = vec![bingo_board_1(), bingo_board_2()];
+        let rules = [WinCondition::Blackout, WinCondition::FourCorners];
+
+        // the four corners of the first board: 22 0 1 19
+        assert_eq!(
+            shortest_winning_draws(&boards, 0, &rules),
+            Some(vec![22, 0, 1, 19])
+        );
+    }
+
+    #[test]
+    fn test_shortest_winning_draws_avoids_other_boards() {
+        // every row and column of the second board contains a row or column
+        // of the first board, except for the last column
+        let boards = vec![
+            parse_board("1 2\n3 4").unwrap(),
+            parse_board("1 2 5\n3 4 6").unwrap(),
+        ];
+
+        assert_eq!(
+            shortest_winning_draws(&boards, 1, STANDARD_RULES),
+            Some(vec![5, 6])
+        );
+    }
+
+    #[test]
+    fn test_shortest_winning_draws_impossible() {
+        // identical boards always win at the same time
+        let boards = vec![bingo_board_1(), bingo_board_3(), bingo_board_1()];
+
+        assert_eq!(shortest_winning_draws(&boards, 0, STANDARD_RULES), None);
+        assert_eq!(
+            shortest_winning_draws(&boards, 1, STANDARD_RULES).map(|draws| draws.len()),
+            Some(5)
+        );
+        assert_eq!(shortest_winning_draws(&boards, 3, STANDARD_RULES), None);
+    }
 }
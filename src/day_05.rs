@@ -87,47 +87,620 @@
 /// 2 or larger - now a total of 12 points.
 ///
 /// Consider all of the lines. At how many points do at least two lines overlap?
+use itertools::Itertools;
 use regex::{Match, Regex};
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 
 const INPUT: &str = include_str!("../input/day_05");
 
-pub fn run() {
+pub fn run(options: &[&str]) {
     let vents: Vec<_> = INPUT.lines().filter_map(HydrothermalVent::parse).collect();
 
-    let map = map_out(vents.iter().filter(|vent| vent.horizontal_or_vertical()));
-    let multiple_vent_points = map
-        .values()
-        .filter(|&amount_of_vents| amount_of_vents > &1)
-        .count();
+    let mut counter = OverlapCounter::Map;
+    let mut rasterisation = Rasterisation::Bresenham;
+    let mut diagram = false;
+    let mut crop = None;
+    let mut exports = vec![];
+    let mut queries = vec![];
+    for &option in options {
+        match option.split_once('=').unwrap_or((option, "")) {
+            // count without mapping out every point
+            ("sparse", "") => counter = OverlapCounter::Sparse,
+            // only the exact points on lines at other angles
+            ("lattice", "") => rasterisation = Rasterisation::LatticePoints,
+            // visualise the map of all lines
+            ("diagram", "") => diagram = true,
+            ("crop", area) => {
+                crop = Some(
+                    BoundingBox::parse(area)
+                        .unwrap_or_else(|| panic!("Invalid area to crop to: {}", area)),
+                )
+            }
+            ("pgm", path) => exports.push((ImageFormat::Pgm, path)),
+            ("png", path) => exports.push((ImageFormat::Png, path)),
+            // ask questions about the vents
+            ("at", point) => queries.push(VentQuery::At(
+                parse_coordinates(point).unwrap_or_else(|| panic!("Invalid point: {}", point)),
+            )),
+            ("min-overlap", amount) => queries.push(VentQuery::MinOverlap(
+                amount
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid amount of vents: {}", amount)),
+            )),
+            ("area", area) => queries.push(VentQuery::Area(
+                BoundingBox::parse(area).unwrap_or_else(|| panic!("Invalid area: {}", area)),
+            )),
+            ("most-dangerous", "") => queries.push(VentQuery::MostDangerous),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    // the sparse counter can't follow Bresenham lines at other angles, those
+    // are mapped out instead
+    let count = |vents: &[&HydrothermalVent]| {
+        counter
+            .count(vents.iter().copied(), rasterisation)
+            .or_else(|| OverlapCounter::Map.count(vents.iter().copied(), rasterisation))
+            .unwrap()
+    };
+
+    let multiple_vent_points = count(
+        &vents
+            .iter()
+            .filter(|vent| vent.orientation() == Orientation::AxisAligned)
+            .collect::<Vec<_>>(),
+    );
 
     println!(
         "There are {} points where multiple vent lines overlap, when considering only horizontal and vertical lines",
         multiple_vent_points
     );
 
-    let all_lines_multiple_vent_points = map_out(vents.iter())
-        .values()
-        .filter(|&amount_of_vents| amount_of_vents > &1)
-        .count();
+    let all_lines_multiple_vent_points = count(&vents.iter().collect::<Vec<_>>());
 
     println!(
         "There are {} points where multiple vent lines overlap, when considering all lines",
         all_lines_multiple_vent_points
     );
+
+    if diagram || !exports.is_empty() {
+        let map = map_out(vents.iter(), rasterisation);
+        let area = crop.or_else(|| BoundingBox::of(&map));
+        match area {
+            Some(area) => {
+                if diagram {
+                    println!("{}", render_diagram(&map, area));
+                }
+                for (format, path) in exports {
+                    match render_image(&map, area, format) {
+                        Some(image) => fs::write(path, image)
+                            .unwrap_or_else(|error| panic!("Could not write {}: {}", path, error)),
+                        None => println!(
+                            "The area of {}x{} points is too large to write to {}",
+                            area.width(),
+                            area.height(),
+                            path
+                        ),
+                    }
+                }
+            }
+            None => println!("There are no vents to visualise"),
+        }
+    }
+
+    if !queries.is_empty() {
+        let index = VentIndex::new(&vents, rasterisation, VENT_INDEX_BUCKET_SIZE);
+        for query in queries {
+            match query {
+                VentQuery::At(point) => println!(
+                    "{:?} is covered by {}",
+                    point,
+                    describe_vents(&index.vents_at(point))
+                ),
+                VentQuery::MinOverlap(amount) => println!(
+                    "There are {} points with at least {} vents",
+                    index.points_with_at_least(amount).len(),
+                    amount
+                ),
+                VentQuery::Area(area) => println!(
+                    "The area from {:?} to {:?} is crossed by {}",
+                    area.top_left,
+                    area.bottom_right,
+                    describe_vents(&index.vents_in(area))
+                ),
+                VentQuery::MostDangerous => match index.most_dangerous_point() {
+                    Some((point, amount)) => println!(
+                        "The most dangerous point is {:?}, with {} vents",
+                        point, amount
+                    ),
+                    None => println!("There are no vents"),
+                },
+            }
+        }
+    }
+}
+
+fn describe_vents(vents: &[&HydrothermalVent]) -> String {
+    let amount = match vents.len() {
+        0 => return "no vents".to_string(),
+        1 => "1 vent".to_string(),
+        n => format!("{} vents", n),
+    };
+    format!(
+        "{}: {}",
+        amount,
+        vents
+            .iter()
+            .map(|vent| format!(
+                "{},{} -> {},{}",
+                vent.end_a.0, vent.end_a.1, vent.end_b.0, vent.end_b.1
+            ))
+            .join(", ")
+    )
+}
+
+/// Parse a point given as x,y.
+fn parse_coordinates(point: &str) -> Option<Coordinates> {
+    let (x, y) = point.split_once(',')?;
+    x.trim().parse().ok().zip(y.trim().parse().ok())
+}
+
+/// Questions that can be asked about the vents from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VentQuery {
+    At(Coordinates),
+    MinOverlap(u32),
+    Area(BoundingBox),
+    MostDangerous,
+}
+
+/// The width and height of the buckets of the vent index.
+const VENT_INDEX_BUCKET_SIZE: i32 = 32;
+
+/// Vents indexed by the square buckets of the map they pass through, so only
+/// the vents near a point or area have to be looked at.
+struct VentIndex<'a> {
+    vents: &'a [HydrothermalVent],
+    rasterisation: Rasterisation,
+    bucket_size: i32,
+    /// indices of the vents passing through each bucket
+    buckets: HashMap<Coordinates, Vec<usize>>,
+    /// all points with vents, the most dangerous first and then from the top
+    /// left
+    dangers: Vec<(Coordinates, u32)>,
+}
+
+impl<'a> VentIndex<'a> {
+    fn new(
+        vents: &'a [HydrothermalVent],
+        rasterisation: Rasterisation,
+        bucket_size: i32,
+    ) -> VentIndex<'a> {
+        let mut buckets: HashMap<Coordinates, Vec<usize>> = HashMap::new();
+        let mut map: HashMap<Coordinates, u32> = HashMap::new();
+        for (index, vent) in vents.iter().enumerate() {
+            let mut passed = HashSet::new();
+            for (x, y) in vent.iter_with(rasterisation) {
+                *map.entry((x, y)).or_insert(0) += 1;
+                passed.insert((x.div_euclid(bucket_size), y.div_euclid(bucket_size)));
+            }
+            for bucket in passed {
+                buckets.entry(bucket).or_default().push(index);
+            }
+        }
+        let mut dangers: Vec<_> = map.into_iter().collect();
+        dangers.sort_unstable_by_key(|&((x, y), amount_of_vents)| (Reverse(amount_of_vents), y, x));
+        VentIndex {
+            vents,
+            rasterisation,
+            bucket_size,
+            buckets,
+            dangers,
+        }
+    }
+
+    /// The vents in the buckets overlapping the area, in the original order.
+    fn candidates(&self, area: BoundingBox) -> Vec<usize> {
+        let bucket = |(x, y): Coordinates| {
+            (
+                x.div_euclid(self.bucket_size),
+                y.div_euclid(self.bucket_size),
+            )
+        };
+        let area = BoundingBox {
+            top_left: bucket(area.top_left),
+            bottom_right: bucket(area.bottom_right),
+        };
+        let mut candidates: Vec<_> = area
+            .rows()
+            .flatten()
+            .filter_map(|bucket| self.buckets.get(&bucket))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+
+    /// The vents covering the point.
+    fn vents_at(&self, point: Coordinates) -> Vec<&'a HydrothermalVent> {
+        self.vents_in(BoundingBox {
+            top_left: point,
+            bottom_right: point,
+        })
+    }
+
+    /// The vents covering at least one point in the area.
+    fn vents_in(&self, area: BoundingBox) -> Vec<&'a HydrothermalVent> {
+        self.candidates(area)
+            .into_iter()
+            .map(|index| &self.vents[index])
+            .filter(|vent| vent.crosses(area, self.rasterisation))
+            .collect()
+    }
+
+    /// All points with at least the given amount of vents, ordered by
+    /// position.
+    fn points_with_at_least(&self, amount: u32) -> Vec<(Coordinates, u32)> {
+        let mut points: Vec<_> = self
+            .dangers
+            .iter()
+            .take_while(|&&(_, amount_of_vents)| amount_of_vents >= amount)
+            .copied()
+            .collect();
+        points.sort_unstable();
+        points
+    }
+
+    /// The point with the most vents, the one closest to the top left when
+    /// there are several.
+    fn most_dangerous_point(&self) -> Option<(Coordinates, u32)> {
+        self.dangers.first().copied()
+    }
+}
+
+/// An area of the vent map, the corners are included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoundingBox {
+    top_left: Coordinates,
+    bottom_right: Coordinates,
+}
+
+impl BoundingBox {
+    /// The smallest area containing all points of the map.
+    fn of(map: &HashMap<Coordinates, u32>) -> Option<BoundingBox> {
+        let (min_x, max_x) = map.keys().map(|(x, _)| *x).minmax().into_option()?;
+        let (min_y, max_y) = map.keys().map(|(_, y)| *y).minmax().into_option()?;
+        Some(BoundingBox {
+            top_left: (min_x, min_y),
+            bottom_right: (max_x, max_y),
+        })
+    }
+
+    /// Parse an area given as x1,y1,x2,y2.
+    fn parse(area: &str) -> Option<BoundingBox> {
+        let corners: Vec<i32> = area
+            .split(',')
+            .map(|n| n.trim().parse())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match corners[..] {
+            [x_a, y_a, x_b, y_b] => Some(BoundingBox {
+                top_left: (x_a.min(x_b), y_a.min(y_b)),
+                bottom_right: (x_a.max(x_b), y_a.max(y_b)),
+            }),
+            _ => None,
+        }
+    }
+
+    fn width(&self) -> u64 {
+        self.top_left.0.abs_diff(self.bottom_right.0) as u64 + 1
+    }
+
+    fn height(&self) -> u64 {
+        self.top_left.1.abs_diff(self.bottom_right.1) as u64 + 1
+    }
+
+    /// All rows of the area from top to bottom, with the points from left to
+    /// right.
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Coordinates>> {
+        let (left, top) = self.top_left;
+        let (right, bottom) = self.bottom_right;
+        (top..=bottom).map(move |y| (left..=right).map(move |x| (x, y)))
+    }
+}
+
+/// Render the map like the diagrams in the puzzle description, points with
+/// more than 9 vents are shown as a '#'.
+fn render_diagram(map: &HashMap<Coordinates, u32>, area: BoundingBox) -> String {
+    area.rows()
+        .map(|row| {
+            row.map(|position| match map.get(&position) {
+                None => '.',
+                Some(&amount) => char::from_digit(amount, 10).unwrap_or('#'),
+            })
+            .collect::<String>()
+        })
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImageFormat {
+    Pgm,
+    Png,
+}
+
+/// Render the map as a grayscale heatmap, the brighter the point the more
+/// vents overlap there. None when the area is too large for an image.
+fn render_image(
+    map: &HashMap<Coordinates, u32>,
+    area: BoundingBox,
+    format: ImageFormat,
+) -> Option<Vec<u8>> {
+    let width = u32::try_from(area.width()).ok()?;
+    let height = u32::try_from(area.height()).ok()?;
+    let max_amount = area
+        .rows()
+        .flatten()
+        .filter_map(|position| map.get(&position))
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+    let pixels: Vec<u8> = area
+        .rows()
+        .flatten()
+        .map(|position| {
+            let amount = map.get(&position).copied().unwrap_or(0);
+            (amount as u64 * 255 / max_amount as u64) as u8
+        })
+        .collect();
+
+    match format {
+        ImageFormat::Pgm => {
+            let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+            image.extend(pixels);
+            Some(image)
+        }
+        ImageFormat::Png => {
+            let mut image = vec![];
+            let mut encoder = png::Encoder::new(&mut image, width, height);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder
+                .write_header()
+                .and_then(|mut writer| writer.write_image_data(&pixels))
+                .expect("Could not encode the heatmap as PNG");
+            Some(image)
+        }
+    }
+}
+
+/// Ways of counting the points where at least two vents overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverlapCounter {
+    /// map out every point of every vent
+    Map,
+    /// only look at the vent end points, see count_overlaps_sparse
+    Sparse,
+}
+
+impl OverlapCounter {
+    /// Count the overlapping points, None when the sparse counter would need
+    /// Bresenham lines at other angles than horizontal, vertical or diagonal.
+    fn count<'a>(
+        &self,
+        vents: impl Iterator<Item = &'a HydrothermalVent>,
+        rasterisation: Rasterisation,
+    ) -> Option<usize> {
+        match self {
+            OverlapCounter::Map => Some(
+                map_out(vents, rasterisation)
+                    .values()
+                    .filter(|&amount_of_vents| amount_of_vents > &1)
+                    .count(),
+            ),
+            OverlapCounter::Sparse => {
+                let vents: Vec<_> = vents.collect();
+                if rasterisation == Rasterisation::Bresenham
+                    && vents
+                        .iter()
+                        .any(|vent| vent.orientation() == Orientation::Other)
+                {
+                    return None;
+                }
+                Some(count_overlaps_sparse(vents.into_iter()))
+            }
+        }
+    }
 }
 
-fn map_out<'a>(vents: impl Iterator<Item = &'a HydrothermalVent>) -> HashMap<Coordinates, u32> {
-    vents
-        .flat_map(|vent| vent.iter())
-        .fold(HashMap::new(), |mut map, position| {
+/// Count the points where at least two vents overlap without visiting all
+/// points of the vents, using the exact lattice points of the vents.
+///
+/// Vents are grouped by the line they're on. A sweep over the vents on the
+/// same line gives the collinear overlaps, the crossings of the different
+/// lines are calculated by intersecting them. Points where lines cross that
+/// are also part of a collinear overlap are only counted once.
+fn count_overlaps_sparse<'a>(vents: impl Iterator<Item = &'a HydrothermalVent>) -> usize {
+    let mut lines: HashMap<VentLine, Vec<Interval>> = HashMap::new();
+    for vent in vents {
+        let (line, interval) = VentLine::of(vent);
+        lines.entry(line).or_default().push(interval);
+    }
+    let lines: Vec<_> = lines
+        .into_iter()
+        .map(|(line, intervals)| {
+            let (covered, overlapping) = sweep(&intervals, line.step());
+            (line, covered, overlapping)
+        })
+        .collect();
+
+    let collinear_overlaps: i128 = lines
+        .iter()
+        .flat_map(|(line, _, overlapping)| {
+            overlapping
+                .iter()
+                .map(move |(start, end)| (end - start) / line.step() + 1)
+        })
+        .sum();
+
+    // for every crossing, the lines on which it's part of a collinear overlap
+    let mut crossings: HashMap<(i128, i128), HashSet<usize>> = HashMap::new();
+    for (i, (line_a, covered_a, overlapping_a)) in lines.iter().enumerate() {
+        for (j, (line_b, covered_b, overlapping_b)) in lines.iter().enumerate().skip(i + 1) {
+            if let Some(point) = line_a.crossing(line_b) {
+                let (position_a, position_b) = (line_a.position(point), line_b.position(point));
+                if in_intervals(covered_a, position_a) && in_intervals(covered_b, position_b) {
+                    let counted_on = crossings.entry(point).or_default();
+                    if in_intervals(overlapping_a, position_a) {
+                        counted_on.insert(i);
+                    }
+                    if in_intervals(overlapping_b, position_b) {
+                        counted_on.insert(j);
+                    }
+                }
+            }
+        }
+    }
+
+    // crossings not counted yet add a point, crossings counted on several
+    // lines were counted too often
+    crossings
+        .values()
+        .fold(collinear_overlaps, |count, counted_on| {
+            match counted_on.len() {
+                0 => count + 1,
+                n => count - (n as i128 - 1),
+            }
+        }) as usize
+}
+
+/// The first and last position of a vent on its line.
+type Interval = (i128, i128);
+
+/// The infinite line a vent lies on, described by the direction of a single
+/// step along it and the cross product of that direction with any point of
+/// the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct VentLine {
+    direction: (i128, i128),
+    offset: i128,
+}
+
+impl VentLine {
+    /// The line of the vent with the interval of positions it covers on it.
+    fn of(vent: &HydrothermalVent) -> (VentLine, Interval) {
+        let a = (vent.end_a.0 as i64, vent.end_a.1 as i64);
+        let b = (vent.end_b.0 as i64, vent.end_b.1 as i64);
+        // the products of long off-angle directions and far away points don't
+        // fit in an i64, so the line is described in i128s
+        let wide = |(x, y): (i64, i64)| (x as i128, y as i128);
+        let mut direction = wide(lattice_step(a, b));
+        let (a, b) = (wide(a), wide(b));
+        // point all directions the same way, so both ends give the same line
+        if direction.0 < 0 || (direction.0 == 0 && direction.1 < 0) {
+            direction = (-direction.0, -direction.1);
+        }
+        // a single point can be seen as being on a horizontal line
+        if direction == (0, 0) {
+            direction = (1, 0);
+        }
+        let line = VentLine {
+            direction,
+            offset: cross(direction, a),
+        };
+        let (start, end) = (line.position(a), line.position(b));
+        (line, (start.min(end), start.max(end)))
+    }
+
+    /// The position of a point on the line, increasing by step() every
+    /// step along the line.
+    fn position(&self, point: (i128, i128)) -> i128 {
+        self.direction.0 * point.0 + self.direction.1 * point.1
+    }
+
+    fn step(&self) -> i128 {
+        self.position(self.direction)
+    }
+
+    /// The point where the lines cross, if they do so on a whole point.
+    fn crossing(&self, other: &VentLine) -> Option<(i128, i128)> {
+        let (dx_a, dy_a) = self.direction;
+        let (dx_b, dy_b) = other.direction;
+        // solve dx * y - dy * x = offset for both lines
+        let determinant = cross(self.direction, other.direction);
+        if determinant == 0 {
+            return None;
+        }
+        let x = self.offset * dx_b - other.offset * dx_a;
+        let y = self.offset * dy_b - other.offset * dy_a;
+        if x % determinant != 0 || y % determinant != 0 {
+            return None;
+        }
+        Some((x / determinant, y / determinant))
+    }
+}
+
+fn cross((x_a, y_a): (i128, i128), (x_b, y_b): (i128, i128)) -> i128 {
+    x_a * y_b - y_a * x_b
+}
+
+/// Sweep over the intervals on a line, giving the intervals covered by at
+/// least one and at least two of them.
+fn sweep(intervals: &[Interval], step: i128) -> (Vec<Interval>, Vec<Interval>) {
+    let mut events: Vec<_> = intervals
+        .iter()
+        .flat_map(|&(start, end)| [(start, 1), (end + step, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut covered: Vec<Interval> = vec![];
+    let mut overlapping: Vec<Interval> = vec![];
+    let mut depth = 0;
+    for (position, change) in events {
+        let previous = depth;
+        depth += change;
+        if previous < 1 && depth >= 1 {
+            covered.push((position, position));
+        } else if previous >= 1 && depth < 1 {
+            covered.last_mut().unwrap().1 = position - step;
+        }
+        if previous < 2 && depth >= 2 {
+            overlapping.push((position, position));
+        } else if previous >= 2 && depth < 2 {
+            overlapping.last_mut().unwrap().1 = position - step;
+        }
+    }
+
+    (covered, overlapping)
+}
+
+/// Is the position in any of the sorted, non overlapping intervals.
+fn in_intervals(intervals: &[Interval], position: i128) -> bool {
+    let index = intervals.partition_point(|&(_, end)| end < position);
+    intervals
+        .get(index)
+        .is_some_and(|&(start, _)| start <= position)
+}
+
+fn map_out<'a>(
+    vents: impl Iterator<Item = &'a HydrothermalVent>,
+    rasterisation: Rasterisation,
+) -> HashMap<Coordinates, u32> {
+    vents.flat_map(|vent| vent.iter_with(rasterisation)).fold(
+        HashMap::new(),
+        |mut map, position| {
             *map.entry(position).or_insert(0) += 1;
             map
-        })
+        },
+    )
 }
 
-type Coordinates = (u32, u32);
+type Coordinates = (i32, i32);
 
 #[derive(Debug, PartialEq)]
 struct HydrothermalVent {
@@ -135,48 +708,191 @@ struct HydrothermalVent {
     end_b: Coordinates,
 }
 
+/// The kind of angle a vent is at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Orientation {
+    /// horizontal or vertical, or a single point
+    AxisAligned,
+    /// at exactly 45 degrees
+    Diagonal,
+    Other,
+}
+
+/// Ways of deciding which points are covered by a vent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rasterisation {
+    /// the points closest to the line, like drawing it with Bresenham's line
+    /// algorithm
+    Bresenham,
+    /// only the points exactly on the line
+    LatticePoints,
+}
+
 impl HydrothermalVent {
-    fn horizontal_or_vertical(&self) -> bool {
+    fn orientation(&self) -> Orientation {
         let (x_a, y_a) = self.end_a;
         let (x_b, y_b) = self.end_b;
 
-        x_a == x_b || y_a == y_b
+        if x_a == x_b || y_a == y_b {
+            Orientation::AxisAligned
+        } else if x_a.abs_diff(x_b) == y_a.abs_diff(y_b) {
+            Orientation::Diagonal
+        } else {
+            Orientation::Other
+        }
     }
 
-    fn iter(&self) -> HydrothermalVentIter {
+    /// Iterate over the points of the vent, starting at end_a. Horizontal,
+    /// vertical and diagonal vents cover the same points in both ways.
+    fn iter_with(&self, rasterisation: Rasterisation) -> HydrothermalVentIter {
+        let a = (self.end_a.0 as i64, self.end_a.1 as i64);
+        let b = (self.end_b.0 as i64, self.end_b.1 as i64);
+        let delta = ((b.0 - a.0).abs(), -(b.1 - a.1).abs());
         HydrothermalVentIter {
             current: Some(self.end_a),
             end: self.end_b,
+            rasterisation,
+            step: match rasterisation {
+                Rasterisation::Bresenham => ((b.0 - a.0).signum(), (b.1 - a.1).signum()),
+                Rasterisation::LatticePoints => lattice_step(a, b),
+            },
+            delta,
+            error: delta.0 + delta.1,
+        }
+    }
+
+    /// Whether the vent covers at least one point in the area, without going
+    /// through its points. The vent is followed in steps along the axis it
+    /// moves furthest along, the steps that stay within the area on that
+    /// axis are a range and so are the positions on the other axis for them.
+    fn crosses(&self, area: BoundingBox, rasterisation: Rasterisation) -> bool {
+        let a = (self.end_a.0 as i64, self.end_a.1 as i64);
+        let b = (self.end_b.0 as i64, self.end_b.1 as i64);
+        let (left, top) = (area.top_left.0 as i64, area.top_left.1 as i64);
+        let (right, bottom) = (area.bottom_right.0 as i64, area.bottom_right.1 as i64);
+        match rasterisation {
+            Rasterisation::LatticePoints => {
+                let step = lattice_step(a, b);
+                let steps = if step.0 != 0 {
+                    (b.0 - a.0) / step.0
+                } else if step.1 != 0 {
+                    (b.1 - a.1) / step.1
+                } else {
+                    0
+                };
+                let (first_x, last_x) = steps_within(a.0, step.0, left, right);
+                let (first_y, last_y) = steps_within(a.1, step.1, top, bottom);
+                first_x.max(first_y).max(0) <= last_x.min(last_y).min(steps)
+            }
+            Rasterisation::Bresenham => {
+                // Bresenham's line algorithm moves one step along the major
+                // axis each time, where the minor axis has moved
+                // floor((2 * minor * step + major) / (2 * major)) by then
+                let x_is_major = (b.0 - a.0).abs() >= (b.1 - a.1).abs();
+                let (major_start, major_end, minor_start, minor_end) = if x_is_major {
+                    (a.0, b.0, a.1, b.1)
+                } else {
+                    (a.1, b.1, a.0, b.0)
+                };
+                let ((major_low, major_high), (minor_low, minor_high)) = if x_is_major {
+                    ((left, right), (top, bottom))
+                } else {
+                    ((top, bottom), (left, right))
+                };
+                let major = (major_end - major_start).abs();
+                let minor = (minor_end - minor_start).abs();
+                let minor_at = |step: i64| {
+                    let moved = if major == 0 {
+                        0
+                    } else {
+                        (2 * minor * step + major) / (2 * major)
+                    };
+                    minor_start + (minor_end - minor_start).signum() * moved
+                };
+                let (first, last) = steps_within(
+                    major_start,
+                    (major_end - major_start).signum(),
+                    major_low,
+                    major_high,
+                );
+                let (first, last) = (first.max(0), last.min(major));
+                if first > last {
+                    return false;
+                }
+                // the minor axis moves by at most one each step, so it
+                // passes every position between the ends
+                let (from, to) = (minor_at(first), minor_at(last));
+                from.min(to) <= minor_high && from.max(to) >= minor_low
+            }
         }
     }
 
     fn parse(line: &str) -> Option<HydrothermalVent> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(r"([0-9]+),([0-9]+) -> ([0-9]+),([0-9]+)").unwrap();
+            static ref RE: Regex =
+                Regex::new(r"(-?[0-9]+),(-?[0-9]+) -> (-?[0-9]+),(-?[0-9]+)").unwrap();
         }
         RE.captures(line).and_then(|groups| {
             (groups
                 .get(1)
-                .and_then(match_as_u32)
-                .zip(groups.get(2).and_then(match_as_u32)))
+                .and_then(match_as_i32)
+                .zip(groups.get(2).and_then(match_as_i32)))
             .zip_with(
                 groups
                     .get(3)
-                    .and_then(match_as_u32)
-                    .zip(groups.get(4).and_then(match_as_u32)),
+                    .and_then(match_as_i32)
+                    .zip(groups.get(4).and_then(match_as_i32)),
                 |end_a, end_b| HydrothermalVent { end_a, end_b },
             )
         })
     }
 }
 
-fn match_as_u32(m: Match) -> Option<u32> {
+fn match_as_i32(m: Match) -> Option<i32> {
     m.as_str().parse().ok()
 }
 
+/// The smallest step from a to b that lands exactly on a point.
+fn lattice_step(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let divisor = gcd(dx.abs(), dy.abs()).max(1);
+    (dx / divisor, dy / divisor)
+}
+
+/// The range of steps from the start that land between low and high, empty
+/// when the first is after the last.
+fn steps_within(start: i64, step: i64, low: i64, high: i64) -> (i64, i64) {
+    match step.signum() {
+        0 if (low..=high).contains(&start) => (i64::MIN, i64::MAX),
+        0 => (1, 0),
+        1 => (
+            -(start - low).div_euclid(step),
+            (high - start).div_euclid(step),
+        ),
+        _ => (
+            -(high - start).div_euclid(-step),
+            (start - low).div_euclid(-step),
+        ),
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
 struct HydrothermalVentIter {
     current: Option<Coordinates>,
     end: Coordinates,
+    rasterisation: Rasterisation,
+    step: (i64, i64),
+    /// absolute distance along x, negated absolute distance along y
+    delta: (i64, i64),
+    /// Bresenham's accumulated error
+    error: i64,
 }
 
 impl Iterator for HydrothermalVentIter {
@@ -184,24 +900,30 @@ impl Iterator for HydrothermalVentIter {
 
     fn next(&mut self) -> Option<Self::Item> {
         let last = self.current;
-        let (end_x, end_y) = self.end;
         self.current = self.current.and_then(|(x, y)| {
-            if x == end_x && y == end_y {
-                None
-            } else {
-                Some((
-                    match x.cmp(&end_x) {
-                        Ordering::Less => x + 1,
-                        Ordering::Equal => x,
-                        Ordering::Greater => x - 1,
-                    },
-                    match y.cmp(&end_y) {
-                        Ordering::Less => y + 1,
-                        Ordering::Equal => y,
-                        Ordering::Greater => y - 1,
-                    },
-                ))
+            if (x, y) == self.end {
+                return None;
+            }
+            let (mut x, mut y) = (x as i64, y as i64);
+            match self.rasterisation {
+                Rasterisation::Bresenham => {
+                    let (dx, dy) = self.delta;
+                    let doubled_error = 2 * self.error;
+                    if doubled_error >= dy {
+                        self.error += dy;
+                        x += self.step.0;
+                    }
+                    if doubled_error <= dx {
+                        self.error += dx;
+                        y += self.step.1;
+                    }
+                }
+                Rasterisation::LatticePoints => {
+                    x += self.step.0;
+                    y += self.step.1;
+                }
             }
+            Some((x as i32, y as i32))
         });
 
         last
@@ -211,6 +933,8 @@ impl Iterator for HydrothermalVentIter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_helpers::Lcg;
+    use test_case::test_case;
 
     #[test]
     fn test_hydrothermal_vent_parse() {
@@ -226,67 +950,106 @@ mod tests {
     }
 
     #[test]
-    fn test_hydrothermal_vent_horizontal_or_vertical() {
+    fn test_hydrothermal_vent_orientation() {
         // 0,9 -> 5,9
-        assert!(HydrothermalVent {
-            end_a: (0, 9),
-            end_b: (5, 9)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (0, 9),
+                end_b: (5, 9)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 8,0 -> 0,8
-        assert!(!HydrothermalVent {
-            end_a: (8, 0),
-            end_b: (0, 8)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (8, 0),
+                end_b: (0, 8)
+            }
+            .orientation(),
+            Orientation::Diagonal
+        );
         // 9,4 -> 3,4
-        assert!(HydrothermalVent {
-            end_a: (9, 4),
-            end_b: (3, 4)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (9, 4),
+                end_b: (3, 4)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 2,2 -> 2,1
-        assert!(HydrothermalVent {
-            end_a: (2, 2),
-            end_b: (2, 1)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (2, 2),
+                end_b: (2, 1)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 7,0 -> 7,4
-        assert!(HydrothermalVent {
-            end_a: (7, 0),
-            end_b: (7, 4)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (7, 0),
+                end_b: (7, 4)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 6,4 -> 2,0
-        assert!(!HydrothermalVent {
-            end_a: (6, 4),
-            end_b: (2, 0)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (6, 4),
+                end_b: (2, 0)
+            }
+            .orientation(),
+            Orientation::Diagonal
+        );
         // 0,9 -> 2,9
-        assert!(HydrothermalVent {
-            end_a: (0, 9),
-            end_b: (2, 9)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (0, 9),
+                end_b: (2, 9)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 3,4 -> 1,4
-        assert!(HydrothermalVent {
-            end_a: (3, 4),
-            end_b: (1, 4)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (3, 4),
+                end_b: (1, 4)
+            }
+            .orientation(),
+            Orientation::AxisAligned
+        );
         // 0,0 -> 8,8
-        assert!(!HydrothermalVent {
-            end_a: (0, 0),
-            end_b: (8, 8)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (0, 0),
+                end_b: (8, 8)
+            }
+            .orientation(),
+            Orientation::Diagonal
+        );
         // 5,5 -> 8,2
-        assert!(!HydrothermalVent {
-            end_a: (5, 5),
-            end_b: (8, 2)
-        }
-        .horizontal_or_vertical());
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (5, 5),
+                end_b: (8, 2)
+            }
+            .orientation(),
+            Orientation::Diagonal
+        );
+        // 0,0 -> 6,3
+        assert_eq!(
+            HydrothermalVent {
+                end_a: (0, 0),
+                end_b: (6, 3)
+            }
+            .orientation(),
+            Orientation::Other
+        );
     }
 
     #[test]
@@ -296,7 +1059,7 @@ mod tests {
             end_a: (1, 1),
             end_b: (1, 3),
         };
-        let mut vent_iter = vent.iter();
+        let mut vent_iter = vent.iter_with(Rasterisation::Bresenham);
 
         assert_eq!(vent_iter.next(), Some((1, 1)));
         assert_eq!(vent_iter.next(), Some((1, 2)));
@@ -311,7 +1074,7 @@ mod tests {
             end_a: (9, 7),
             end_b: (7, 7),
         };
-        let mut vent_iter = vent.iter();
+        let mut vent_iter = vent.iter_with(Rasterisation::Bresenham);
 
         assert_eq!(vent_iter.next(), Some((9, 7)));
         assert_eq!(vent_iter.next(), Some((8, 7)));
@@ -326,7 +1089,7 @@ mod tests {
             end_a: (1, 1),
             end_b: (3, 3),
         };
-        let mut vent_iter = vent.iter();
+        let mut vent_iter = vent.iter_with(Rasterisation::Bresenham);
 
         assert_eq!(vent_iter.next(), Some((1, 1)));
         assert_eq!(vent_iter.next(), Some((2, 2)));
@@ -341,7 +1104,7 @@ mod tests {
             end_a: (9, 7),
             end_b: (7, 9),
         };
-        let mut vent_iter = vent.iter();
+        let mut vent_iter = vent.iter_with(Rasterisation::Bresenham);
 
         assert_eq!(vent_iter.next(), Some((9, 7)));
         assert_eq!(vent_iter.next(), Some((8, 8)));
@@ -351,7 +1114,7 @@ mod tests {
 
     #[test]
     fn test_map_out() {
-        let vents = vec![
+        let vents = [
             HydrothermalVent {
                 end_a: (0, 9),
                 end_b: (5, 9),
@@ -414,6 +1177,537 @@ mod tests {
         .into_iter()
         .collect();
 
-        assert_eq!(map_out(vents.iter()), expected_map);
+        assert_eq!(
+            map_out(vents.iter(), Rasterisation::Bresenham),
+            expected_map
+        );
+    }
+
+    fn example_vents() -> Vec<HydrothermalVent> {
+        "0,9 -> 5,9\n\
+         8,0 -> 0,8\n\
+         9,4 -> 3,4\n\
+         2,2 -> 2,1\n\
+         7,0 -> 7,4\n\
+         6,4 -> 2,0\n\
+         0,9 -> 2,9\n\
+         3,4 -> 1,4\n\
+         0,0 -> 8,8\n\
+         5,5 -> 8,2\n"
+            .lines()
+            .filter_map(HydrothermalVent::parse)
+            .collect()
+    }
+
+    /// Vents from a simple pseudo random generator, to compare counters.
+    /// The vents are spread around the origin.
+    fn generated_vents(seed: u64, amount: usize, size: u32) -> Vec<HydrothermalVent> {
+        let mut lcg = Lcg::new(seed);
+        let mut next = |bound: u32| lcg.below(bound);
+        (0..amount)
+            .map(|_| {
+                let end_a = (next(size), next(size));
+                let length = next(size);
+                let (x, y) = end_a;
+                let end_b = match next(5) {
+                    0 => (length, y),
+                    1 => (x, length),
+                    2 => {
+                        let length = length.min(size - 1 - x).min(size - 1 - y);
+                        (x + length, y + length)
+                    }
+                    3 => {
+                        let length = length.min(size - 1 - x).min(y);
+                        (x + length, y - length)
+                    }
+                    _ => (next(size), next(size)),
+                };
+                let offset = (size / 2) as i32;
+                let shift = |(x, y): (u32, u32)| (x as i32 - offset, y as i32 - offset);
+                HydrothermalVent {
+                    end_a: shift(end_a),
+                    end_b: shift(end_b),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_count_overlaps_example() {
+        let vents = example_vents();
+
+        for counter in [OverlapCounter::Map, OverlapCounter::Sparse] {
+            for rasterisation in [Rasterisation::Bresenham, Rasterisation::LatticePoints] {
+                assert_eq!(
+                    counter.count(
+                        vents
+                            .iter()
+                            .filter(|vent| vent.orientation() == Orientation::AxisAligned),
+                        rasterisation
+                    ),
+                    Some(5)
+                );
+                assert_eq!(counter.count(vents.iter(), rasterisation), Some(12));
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_overlaps_sparse_collinear() {
+        let vents = [
+            HydrothermalVent {
+                end_a: (0, 0),
+                end_b: (6, 6),
+            },
+            HydrothermalVent {
+                end_a: (8, 8),
+                end_b: (2, 2),
+            },
+            HydrothermalVent {
+                end_a: (4, 4),
+                end_b: (4, 4),
+            },
+            // crosses in the middle of the overlap
+            HydrothermalVent {
+                end_a: (0, 8),
+                end_b: (8, 0),
+            },
+        ];
+
+        // 2,2 up to 6,6 overlap
+        assert_eq!(count_overlaps_sparse(vents.iter()), 5);
+    }
+
+    #[test]
+    fn test_count_overlaps_sparse_crossing_overlaps() {
+        let vents = [
+            // 5,4 up to 9,0 overlap
+            HydrothermalVent {
+                end_a: (1, 8),
+                end_b: (9, 0),
+            },
+            HydrothermalVent {
+                end_a: (5, 4),
+                end_b: (9, 0),
+            },
+            // 6,0 up to 10,0 overlap, sharing 9,0 with the overlap above
+            HydrothermalVent {
+                end_a: (6, 0),
+                end_b: (10, 0),
+            },
+            HydrothermalVent {
+                end_a: (6, 0),
+                end_b: (11, 0),
+            },
+        ];
+
+        assert_eq!(count_overlaps_sparse(vents.iter()), 9);
+        assert_eq!(
+            OverlapCounter::Map.count(vents.iter(), Rasterisation::LatticePoints),
+            Some(9)
+        );
+    }
+
+    #[test]
+    fn test_count_overlaps_sparse_crossing_between_points() {
+        // these diagonals cross between 0,0 -> 1,1 and 0,1 -> 1,0
+        let vents = [
+            HydrothermalVent {
+                end_a: (0, 0),
+                end_b: (1, 1),
+            },
+            HydrothermalVent {
+                end_a: (0, 1),
+                end_b: (1, 0),
+            },
+        ];
+
+        assert_eq!(count_overlaps_sparse(vents.iter()), 0);
+    }
+
+    #[test]
+    fn test_count_overlaps_sparse_long_off_angle_vents() {
+        // nearly parallel vents that only share an end, their lines pass far
+        // enough from the origin that crossing them overflows an i64
+        let vents = [
+            HydrothermalVent {
+                end_a: (1000000000, -1000000000),
+                end_b: (1003000000, -997000001),
+            },
+            HydrothermalVent {
+                end_a: (1003000000, -997000001),
+                end_b: (1005999999, -994000003),
+            },
+        ];
+
+        assert_eq!(
+            OverlapCounter::Sparse.count(vents.iter(), Rasterisation::LatticePoints),
+            OverlapCounter::Map.count(vents.iter(), Rasterisation::LatticePoints)
+        );
+        assert_eq!(count_overlaps_sparse(vents.iter()), 1);
+    }
+
+    #[test_case(1, 20, 10 ; "few small vents")]
+    #[test_case(2, 100, 20 ; "many small vents")]
+    #[test_case(3, 200, 100 ; "many large vents")]
+    #[test_case(4, 500, 50 ; "crowded vents")]
+    fn test_count_overlaps_equivalence(seed: u64, amount: usize, size: u32) {
+        let vents = generated_vents(seed, amount, size);
+
+        assert_eq!(
+            OverlapCounter::Sparse.count(vents.iter(), Rasterisation::LatticePoints),
+            OverlapCounter::Map.count(vents.iter(), Rasterisation::LatticePoints)
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_iter_bresenham() {
+        let vent = HydrothermalVent {
+            end_a: (0, 0),
+            end_b: (5, 2),
+        };
+
+        assert_eq!(
+            vent.iter_with(Rasterisation::Bresenham).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_iter_bresenham_steep() {
+        let vent = HydrothermalVent {
+            end_a: (3, 6),
+            end_b: (1, 0),
+        };
+
+        assert_eq!(
+            vent.iter_with(Rasterisation::Bresenham).collect::<Vec<_>>(),
+            vec![(3, 6), (3, 5), (2, 4), (2, 3), (2, 2), (1, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_iter_lattice_points() {
+        let vent = HydrothermalVent {
+            end_a: (6, 3),
+            end_b: (0, 0),
+        };
+
+        assert_eq!(
+            vent.iter_with(Rasterisation::LatticePoints)
+                .collect::<Vec<_>>(),
+            vec![(6, 3), (4, 2), (2, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_iter_lattice_points_only_ends() {
+        let vent = HydrothermalVent {
+            end_a: (0, 0),
+            end_b: (5, 2),
+        };
+
+        assert_eq!(
+            vent.iter_with(Rasterisation::LatticePoints)
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (5, 2)]
+        );
+    }
+
+    #[test]
+    fn test_count_overlaps_sparse_needs_lattice_points() {
+        let vents = [
+            // covers 0,0 1,1 2,1 3,2 and 4,2 with Bresenham
+            HydrothermalVent {
+                end_a: (0, 0),
+                end_b: (4, 2),
+            },
+            HydrothermalVent {
+                end_a: (0, 1),
+                end_b: (4, 1),
+            },
+        ];
+
+        assert_eq!(
+            OverlapCounter::Sparse.count(vents.iter(), Rasterisation::Bresenham),
+            None
+        );
+        assert_eq!(
+            OverlapCounter::Map.count(vents.iter(), Rasterisation::Bresenham),
+            Some(2)
+        );
+        // only 2,1 is exactly on the line
+        assert_eq!(
+            OverlapCounter::Sparse.count(vents.iter(), Rasterisation::LatticePoints),
+            Some(1)
+        );
+        assert_eq!(
+            OverlapCounter::Map.count(vents.iter(), Rasterisation::LatticePoints),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_parse_negative() {
+        let input = "-3,2 -> 4,-5";
+
+        assert_eq!(
+            HydrothermalVent::parse(input),
+            Some(HydrothermalVent {
+                end_a: (-3, 2),
+                end_b: (4, -5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_hydrothermal_vent_iter_through_origin() {
+        let vent = HydrothermalVent {
+            end_a: (2, -2),
+            end_b: (-2, 2),
+        };
+
+        assert_eq!(
+            vent.iter_with(Rasterisation::Bresenham).collect::<Vec<_>>(),
+            vec![(2, -2), (1, -1), (0, 0), (-1, 1), (-2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_map_out_crossing_origin() {
+        let vents = [
+            HydrothermalVent {
+                end_a: (-2, 0),
+                end_b: (2, 0),
+            },
+            HydrothermalVent {
+                end_a: (0, -1),
+                end_b: (0, 1),
+            },
+            HydrothermalVent {
+                end_a: (-1, -1),
+                end_b: (1, 1),
+            },
+        ];
+
+        let expected_map = [
+            ((-2, 0), 1),
+            ((-1, 0), 1),
+            ((0, 0), 3),
+            ((1, 0), 1),
+            ((2, 0), 1),
+            ((0, -1), 1),
+            ((0, 1), 1),
+            ((-1, -1), 1),
+            ((1, 1), 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            map_out(vents.iter(), Rasterisation::Bresenham),
+            expected_map
+        );
+        assert_eq!(count_overlaps_sparse(vents.iter()), 1);
+    }
+
+    #[test]
+    fn test_render_diagram() {
+        let vents = example_vents();
+        let map = map_out(vents.iter(), Rasterisation::Bresenham);
+
+        let expected_diagram = "\
+            1.1....11.\n\
+            .111...2..\n\
+            ..2.1.111.\n\
+            ...1.2.2..\n\
+            .112313211\n\
+            ...1.2....\n\
+            ..1...1...\n\
+            .1.....1..\n\
+            1.......1.\n\
+            222111....";
+
+        let area = BoundingBox::of(&map).unwrap();
+        assert_eq!(area, BoundingBox::parse("0,0,9,9").unwrap());
+        assert_eq!(render_diagram(&map, area), expected_diagram);
+    }
+
+    #[test]
+    fn test_render_diagram_cropped() {
+        let vents = example_vents();
+        let map = map_out(vents.iter(), Rasterisation::Bresenham);
+
+        let area = BoundingBox::parse("3,5,1,3").unwrap();
+        assert_eq!(render_diagram(&map, area), "..1\n112\n..1");
+    }
+
+    #[test]
+    fn test_bounding_box_parse() {
+        assert_eq!(
+            BoundingBox::parse("-2,5,3,-1"),
+            Some(BoundingBox {
+                top_left: (-2, -1),
+                bottom_right: (3, 5)
+            })
+        );
+        assert_eq!(BoundingBox::parse("1,2,3"), None);
+        assert_eq!(BoundingBox::parse("1,2,3,x"), None);
+    }
+
+    #[test]
+    fn test_render_image_pgm() {
+        let map = [((0, 0), 1), ((1, 0), 2), ((1, 1), 1)]
+            .into_iter()
+            .collect();
+        let area = BoundingBox::of(&map).unwrap();
+
+        assert_eq!(
+            render_image(&map, area, ImageFormat::Pgm),
+            Some(b"P5\n2 2\n255\n\x7f\xff\x00\x7f".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_render_image_png() {
+        let map = [((0, 0), 1), ((1, 0), 2), ((1, 1), 1)]
+            .into_iter()
+            .collect();
+        let area = BoundingBox::of(&map).unwrap();
+
+        let image = render_image(&map, area, ImageFormat::Png).unwrap();
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(image))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!(pixels, vec![0x7f, 0xff, 0x00, 0x7f]);
+    }
+
+    #[test_case(ImageFormat::Pgm ; "pgm")]
+    #[test_case(ImageFormat::Png ; "png")]
+    fn test_render_image_too_large(format: ImageFormat) {
+        let area = BoundingBox {
+            top_left: (i32::MIN, 0),
+            bottom_right: (i32::MAX, 0),
+        };
+
+        assert_eq!(area.width(), 1 << 32);
+        assert_eq!(render_image(&HashMap::new(), area, format), None);
+    }
+
+    #[test]
+    fn test_vent_index_vents_at() {
+        let vents = example_vents();
+        let index = VentIndex::new(&vents, Rasterisation::Bresenham, 4);
+
+        // 0,9 -> 5,9 and 0,9 -> 2,9
+        assert_eq!(index.vents_at((1, 9)), vec![&vents[0], &vents[6]]);
+        // 8,0 -> 0,8, 9,4 -> 3,4 and 0,0 -> 8,8
+        assert_eq!(
+            index.vents_at((4, 4)),
+            vec![&vents[1], &vents[2], &vents[8]]
+        );
+        assert_eq!(index.vents_at((9, 9)), Vec::<&HydrothermalVent>::new());
+        assert_eq!(index.vents_at((-5, 3)), Vec::<&HydrothermalVent>::new());
+    }
+
+    #[test]
+    fn test_vent_index_vents_in() {
+        let vents = example_vents();
+        let index = VentIndex::new(&vents, Rasterisation::Bresenham, 4);
+
+        // 8,0 -> 0,8 and 6,4 -> 2,0, 5,5 -> 8,2 passes just outside at 7,3
+        let area = BoundingBox::parse("5,1,6,3").unwrap();
+        assert_eq!(index.vents_in(area), vec![&vents[1], &vents[5]]);
+
+        // also 7,0 -> 7,4 and 5,5 -> 8,2
+        let area = BoundingBox::parse("5,1,7,3").unwrap();
+        assert_eq!(
+            index.vents_in(area),
+            vec![&vents[1], &vents[4], &vents[5], &vents[9]]
+        );
+    }
+
+    #[test]
+    fn test_vent_index_points_with_at_least() {
+        let vents = example_vents();
+        let index = VentIndex::new(&vents, Rasterisation::Bresenham, 4);
+
+        assert_eq!(index.points_with_at_least(2).len(), 12);
+        assert_eq!(
+            index.points_with_at_least(3),
+            vec![((4, 4), 3), ((6, 4), 3)]
+        );
+        assert_eq!(index.points_with_at_least(4), vec![]);
+    }
+
+    #[test]
+    fn test_vent_index_most_dangerous_point() {
+        let vents = example_vents();
+        let index = VentIndex::new(&vents, Rasterisation::Bresenham, 4);
+
+        // 6,4 has as many vents, but 4,4 is more to the left
+        assert_eq!(index.most_dangerous_point(), Some(((4, 4), 3)));
+        assert_eq!(
+            VentIndex::new(&[], Rasterisation::Bresenham, 4).most_dangerous_point(),
+            None
+        );
+    }
+
+    #[test_case(Rasterisation::Bresenham ; "bresenham")]
+    #[test_case(Rasterisation::LatticePoints ; "lattice points")]
+    fn test_hydrothermal_vent_crosses(rasterisation: Rasterisation) {
+        let vents = generated_vents(7, 200, 30);
+        let mut lcg = Lcg::new(11);
+
+        for vent in &vents {
+            for _ in 0..10 {
+                let corner = |lcg: &mut Lcg| (lcg.below(40) as i32 - 20, lcg.below(40) as i32 - 20);
+                let (a, b) = (corner(&mut lcg), corner(&mut lcg));
+                let area = BoundingBox {
+                    top_left: (a.0.min(b.0), a.1.min(b.1)),
+                    bottom_right: (a.0.max(b.0), a.1.max(b.1)),
+                };
+                assert_eq!(
+                    vent.crosses(area, rasterisation),
+                    vent.iter_with(rasterisation)
+                        .any(|point| area.rows().flatten().contains(&point)),
+                    "{:?} in {:?}",
+                    vent,
+                    area
+                );
+            }
+        }
+    }
+
+    #[test_case(1 ; "bucket size 1")]
+    #[test_case(3 ; "bucket size 3")]
+    #[test_case(64 ; "bucket size 64")]
+    fn test_vent_index_matches_map(bucket_size: i32) {
+        let vents = generated_vents(5, 50, 40);
+        let index = VentIndex::new(&vents, Rasterisation::Bresenham, bucket_size);
+        let map = map_out(vents.iter(), Rasterisation::Bresenham);
+
+        for point in BoundingBox::parse("-20,-20,20,20")
+            .unwrap()
+            .rows()
+            .flatten()
+        {
+            assert_eq!(
+                index.vents_at(point).len() as u32,
+                map.get(&point).copied().unwrap_or(0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(parse_coordinates("3,-4"), Some((3, -4)));
+        assert_eq!(parse_coordinates("3"), None);
+        assert_eq!(parse_coordinates("3,y"), None);
     }
 }
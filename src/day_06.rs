@@ -74,10 +74,14 @@
 ///
 /// Find a way to simulate lanternfish. How many lanternfish would there be
 /// after 80 days?
+use num_bigint::BigUint;
+use num_traits::{CheckedAdd, CheckedMul, Zero};
+use std::fmt;
+use std::fs;
 
 const INPUT: &str = include_str!("../input/day_06");
 
-pub fn run() {
+pub fn run(options: &[&str]) {
     let mut fish_ages = load_fish_ages(INPUT);
 
     // simulate 80 days
@@ -100,6 +104,105 @@ pub fn run() {
         "There are {} lanternfish after 256 days",
         fish_ages.iter().sum::<u64>()
     );
+
+    let mut projections = Vec::new();
+    let mut exact = false;
+    let mut species = Vec::new();
+    let mut series_days = 256;
+    let mut series_exports = Vec::new();
+    for &option in options {
+        match option.split_once('=').unwrap_or((option, "")) {
+            // project any amount of days
+            ("days", days) => projections.push(
+                days.parse()
+                    .unwrap_or_else(|_| panic!("Invalid amount of days: {}", days)),
+            ),
+            // count the projections exactly, however long they get
+            ("exact", "") => exact = true,
+            // the first day with more fish than the count
+            ("exceeds", count) => {
+                let count = count
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid amount of fish: {}", count));
+                match first_day_exceeding(load_fish_ages(INPUT), &count) {
+                    Some(day) => println!(
+                        "There are more than {} lanternfish after {} days",
+                        count, day
+                    ),
+                    None => println!("There will never be more than {} lanternfish", count),
+                }
+            }
+            // simulate other species as cycle/delay[/lifespan]
+            ("species", description) => species.push(
+                Species::parse(description)
+                    .unwrap_or_else(|| panic!("Invalid species: {}", description)),
+            ),
+            // the population of every day
+            ("series", days) => {
+                series_days = days
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid amount of days: {}", days))
+            }
+            ("csv", "") => series_exports.push(SeriesFormat::Csv),
+            ("json", "") => series_exports.push(SeriesFormat::Json),
+            ("chart", "") => series_exports.push(SeriesFormat::Chart),
+            ("svg", path) => series_exports.push(SeriesFormat::Svg(path)),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    for days in projections {
+        match project_population(load_fish_ages(INPUT), days, exact) {
+            Some(population) => println!("There are {} lanternfish after {} days", population, days),
+            None => println!(
+                "There are too many lanternfish to count quickly after {} days, add exact to count them anyway",
+                days
+            ),
+        }
+    }
+
+    if !series_exports.is_empty() {
+        let series = population_series(load_fish_ages(INPUT), series_days).unwrap_or_else(|| {
+            panic!(
+                "There are too many lanternfish to count within {} days",
+                series_days
+            )
+        });
+        for format in series_exports {
+            match format {
+                SeriesFormat::Csv => print!("{}", render_csv(&series)),
+                SeriesFormat::Json => println!("{}", render_json(&series)),
+                SeriesFormat::Chart => print!("{}", render_chart(&series, CHART_WIDTH)),
+                SeriesFormat::Svg(path) => fs::write(path, render_svg(&series))
+                    .unwrap_or_else(|error| panic!("Could not write {}: {}", path, error)),
+            }
+        }
+    }
+
+    if !species.is_empty() {
+        if let Err(error) = print_species(Model::new(species)) {
+            println!("Could not simulate the species: {}", error);
+        }
+    }
+}
+
+/// Print the population of each species after 80 and 256 days.
+fn print_species(model: Model) -> Result<(), SimulationError> {
+    let mut populations = model.populate(&load_fish_ages(INPUT))?;
+    for day in 1..=256 {
+        populations = model.simulate_day(populations)?;
+        if day == 80 || day == 256 {
+            for (species, population) in model.species.iter().zip(&populations) {
+                println!(
+                    "There are {} fish of species {} after {} days",
+                    count_population(population)?,
+                    species,
+                    day
+                );
+            }
+        }
+    }
+    Ok(())
 }
 
 type FishAges = [u64; 9];
@@ -117,15 +220,541 @@ fn load_fish_ages(input: &str) -> FishAges {
     fish_ages
 }
 
-fn simulate_day(mut fish_ages: FishAges) -> FishAges {
-    fish_ages.rotate_left(1);
-    fish_ages[6] += fish_ages[8];
-    fish_ages
+fn simulate_day(fish_ages: FishAges) -> FishAges {
+    LANTERNFISH
+        .lifecycle()
+        .simulate_day(&fish_ages)
+        .unwrap_or_else(|error| panic!("Could not simulate the lanternfish: {}", error))
+        .try_into()
+        .expect("Expected the lanternfish to have 9 timers")
+}
+
+/// How a species of fish reproduces and how long it lives.
+#[derive(Debug, Clone, PartialEq)]
+struct Species {
+    /// the days between creating new fish
+    cycle: usize,
+    /// the extra days a new fish needs before its first cycle
+    newborn_delay: usize,
+    /// the amount of days a fish lives, if they don't live forever
+    lifespan: Option<usize>,
+}
+
+const LANTERNFISH: Species = Species {
+    cycle: 7,
+    newborn_delay: 2,
+    lifespan: None,
+};
+
+impl Species {
+    /// Parse a species as cycle/delay or cycle/delay/lifespan, e.g. 7/2/50.
+    fn parse(description: &str) -> Option<Species> {
+        let mut numbers = description.split('/').map(str::parse::<usize>);
+        let species = Species {
+            cycle: numbers.next()?.ok()?,
+            newborn_delay: numbers.next()?.ok()?,
+            lifespan: numbers.next().transpose().ok()?,
+        };
+        if species.cycle == 0 || numbers.next().is_some() {
+            return None;
+        }
+        Some(species)
+    }
+
+    /// The timer a new fish starts with.
+    fn newborn_timer(&self) -> usize {
+        self.cycle + self.newborn_delay - 1
+    }
+
+    /// The states the fish of this species can be in. Immortal fish only
+    /// need their timer, so each state is a timer. Mortal fish also need
+    /// their age, which decides their timer as well, so each state is an age.
+    fn lifecycle(&self) -> Lifecycle {
+        let newborn_timer = self.newborn_timer();
+        match self.lifespan {
+            None => Lifecycle {
+                next: (0..=newborn_timer)
+                    .map(|timer| {
+                        Some(if timer == 0 {
+                            self.cycle - 1
+                        } else {
+                            timer - 1
+                        })
+                    })
+                    .collect(),
+                spawns: (0..=newborn_timer).map(|timer| timer == 0).collect(),
+                newborn: newborn_timer,
+            },
+            Some(lifespan) => Lifecycle {
+                next: (0..lifespan)
+                    .map(|age| Some(age + 1).filter(|&age| age < lifespan))
+                    .collect(),
+                spawns: (0..lifespan)
+                    .map(|age| {
+                        age >= newborn_timer && (age - newborn_timer).is_multiple_of(self.cycle)
+                    })
+                    .collect(),
+                newborn: 0,
+            },
+        }
+    }
+
+    /// The state of a fish with the timer. A mortal fish is taken to be as
+    /// young as possible for its timer, None when it would already be dead.
+    fn state(&self, timer: usize) -> Result<Option<usize>, SimulationError> {
+        let newborn_timer = self.newborn_timer();
+        if timer > newborn_timer {
+            return Err(SimulationError::TimerTooLong {
+                timer,
+                species: self.clone(),
+            });
+        }
+        Ok(match self.lifespan {
+            None => Some(timer),
+            Some(lifespan) => Some(newborn_timer - timer).filter(|&age| age < lifespan),
+        })
+    }
+}
+
+impl fmt::Display for Species {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.cycle, self.newborn_delay)?;
+        if let Some(lifespan) = self.lifespan {
+            write!(f, "/{}", lifespan)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reasons a species can't be simulated.
+#[derive(Debug, PartialEq)]
+enum SimulationError {
+    /// a fish starts with a timer that fish of the species never have
+    TimerTooLong { timer: usize, species: Species },
+    /// there are more fish than fit in the count
+    Overflow,
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulationError::TimerTooLong { timer, species } => write!(
+                f,
+                "a timer of {} is longer than the newborn timer of species {}",
+                timer, species
+            ),
+            SimulationError::Overflow => write!(f, "there are too many fish to count"),
+        }
+    }
+}
+
+/// The amount of fish in every state of a lifecycle.
+type Population = Vec<u64>;
+
+/// The amount of fish in all states together.
+fn count_population(population: &Population) -> Result<u64, SimulationError> {
+    population
+        .iter()
+        .try_fold(0u64, |total, &amount| total.checked_add(amount))
+        .ok_or(SimulationError::Overflow)
+}
+
+/// The states of a species and how fish move through them every day.
+#[derive(Debug, PartialEq)]
+struct Lifecycle {
+    /// the state a fish in each state moves to, None when it dies
+    next: Vec<Option<usize>>,
+    /// whether a fish in each state creates a new fish that day
+    spawns: Vec<bool>,
+    /// the state new fish start in
+    newborn: usize,
+}
+
+impl Lifecycle {
+    fn simulate_day(&self, population: &[u64]) -> Result<Population, SimulationError> {
+        let mut next_population = vec![0u64; self.next.len()];
+        let mut add = |state: usize, amount: u64| {
+            next_population[state] = next_population[state]
+                .checked_add(amount)
+                .ok_or(SimulationError::Overflow)?;
+            Ok(())
+        };
+        for (state, &amount) in population.iter().enumerate() {
+            if let Some(next) = self.next[state] {
+                add(next, amount)?;
+            }
+            if self.spawns[state] {
+                add(self.newborn, amount)?;
+            }
+        }
+        Ok(next_population)
+    }
+}
+
+/// Multiple species living together, each reproducing on their own.
+struct Model {
+    species: Vec<Species>,
+    lifecycles: Vec<Lifecycle>,
+}
+
+impl Model {
+    fn new(species: Vec<Species>) -> Model {
+        let lifecycles = species.iter().map(Species::lifecycle).collect();
+        Model {
+            species,
+            lifecycles,
+        }
+    }
+
+    /// The population of each species when they all start with the fish ages.
+    /// None of the fish ages can be longer than the newborn timers.
+    fn populate(&self, fish_ages: &FishAges) -> Result<Vec<Population>, SimulationError> {
+        self.species
+            .iter()
+            .zip(&self.lifecycles)
+            .map(|(species, lifecycle)| {
+                let mut population = vec![0u64; lifecycle.next.len()];
+                for (timer, &amount) in fish_ages.iter().enumerate() {
+                    if amount == 0 {
+                        continue;
+                    }
+                    if let Some(state) = species.state(timer)? {
+                        population[state] = population[state]
+                            .checked_add(amount)
+                            .ok_or(SimulationError::Overflow)?;
+                    }
+                }
+                Ok(population)
+            })
+            .collect()
+    }
+
+    fn simulate_day(
+        &self,
+        populations: Vec<Population>,
+    ) -> Result<Vec<Population>, SimulationError> {
+        self.lifecycles
+            .iter()
+            .zip(populations)
+            .map(|(lifecycle, population)| lifecycle.simulate_day(&population))
+            .collect()
+    }
+}
+
+enum SeriesFormat<'a> {
+    Csv,
+    Json,
+    Chart,
+    Svg(&'a str),
+}
+
+/// The fish ages of every day, starting with the fish ages given. None when
+/// the population grows too large to count in a u64 within the days.
+fn population_series(fish_ages: FishAges, days: usize) -> Option<Vec<FishAges>> {
+    // the renderers add up the ages of a day as well
+    let counted = |fish_ages: FishAges| {
+        fish_ages
+            .iter()
+            .try_fold(0u64, |total, &amount| total.checked_add(amount))
+            .map(|_| fish_ages)
+    };
+    let lifecycle = LANTERNFISH.lifecycle();
+    let mut series = Vec::with_capacity(days + 1);
+    series.push(counted(fish_ages)?);
+    for day in 0..days {
+        let next = lifecycle.simulate_day(&series[day]).ok()?;
+        series.push(counted(next.try_into().ok()?)?);
+    }
+    Some(series)
+}
+
+/// How much the population grew from the day before, None on the first day
+/// or when there were no fish the day before.
+fn growth_ratios(series: &[FishAges]) -> Vec<Option<f64>> {
+    let totals: Vec<u64> = series
+        .iter()
+        .map(|fish_ages| fish_ages.iter().sum())
+        .collect();
+    let mut ratios = vec![None];
+    ratios.extend(
+        totals
+            .windows(2)
+            .map(|pair| (pair[0] > 0).then(|| pair[1] as f64 / pair[0] as f64)),
+    );
+    ratios.truncate(series.len());
+    ratios
+}
+
+fn render_csv(series: &[FishAges]) -> String {
+    let mut csv = String::from("day");
+    for age in 0..9 {
+        csv.push_str(&format!(",age_{}", age));
+    }
+    csv.push_str(",total,growth\n");
+    for (day, (fish_ages, growth)) in series.iter().zip(growth_ratios(series)).enumerate() {
+        csv.push_str(&day.to_string());
+        for amount in fish_ages {
+            csv.push_str(&format!(",{}", amount));
+        }
+        csv.push_str(&format!(",{},", fish_ages.iter().sum::<u64>()));
+        if let Some(growth) = growth {
+            csv.push_str(&format!("{:.6}", growth));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+fn render_json(series: &[FishAges]) -> String {
+    let days: Vec<String> = series
+        .iter()
+        .zip(growth_ratios(series))
+        .enumerate()
+        .map(|(day, (fish_ages, growth))| {
+            format!(
+                "{{\"day\":{},\"ages\":[{}],\"total\":{},\"growth\":{}}}",
+                day,
+                fish_ages.map(|amount| amount.to_string()).join(","),
+                fish_ages.iter().sum::<u64>(),
+                growth.map_or("null".to_string(), |growth| format!("{:.6}", growth))
+            )
+        })
+        .collect();
+    format!("[{}]", days.join(","))
+}
+
+/// The width of the bars in the ASCII chart.
+const CHART_WIDTH: usize = 60;
+
+/// The start and end of every age in a bar of the width, the ages stacked
+/// in order and each taking its share of the population.
+fn stack(fish_ages: &FishAges, width: usize) -> [(usize, usize); 9] {
+    let total = fish_ages.iter().sum::<u64>() as u128;
+    let mut stack = [(0, 0); 9];
+    let mut cumulative = 0;
+    for (age, &amount) in fish_ages.iter().enumerate() {
+        let start = stack[age.saturating_sub(1)].1;
+        cumulative += amount as u128;
+        let end = if total == 0 {
+            0
+        } else {
+            // round to the nearest column, so the bar always fills the width
+            ((cumulative * width as u128 * 2 + total) / (total * 2)) as usize
+        };
+        stack[age] = (start, end);
+    }
+    stack
+}
+
+/// A bar for every day, filled with the ages by their share of the
+/// population, followed by the total and the growth from the day before.
+fn render_chart(series: &[FishAges], width: usize) -> String {
+    let mut chart = String::new();
+    for (day, (fish_ages, growth)) in series.iter().zip(growth_ratios(series)).enumerate() {
+        chart.push_str(&format!("{:>4} |", day));
+        for (age, (start, end)) in stack(fish_ages, width).iter().enumerate() {
+            chart.extend((*start..*end).map(|_| char::from(b'0' + age as u8)));
+        }
+        chart.push_str(&format!("| {}", fish_ages.iter().sum::<u64>()));
+        if let Some(growth) = growth {
+            chart.push_str(&format!(" x{:.3}", growth));
+        }
+        chart.push('\n');
+    }
+    chart
+}
+
+/// The colours of the ages in the SVG chart.
+const AGE_COLOURS: [&str; 9] = [
+    "#d73027", "#f46d43", "#fdae61", "#fee090", "#ffffbf", "#e0f3f8", "#abd9e9", "#74add1",
+    "#4575b4",
+];
+
+/// A column for every day, stacked with the share of every age, with the
+/// growth from the day before as a line over it.
+fn render_svg(series: &[FishAges]) -> String {
+    let height = 200;
+    let column_width = 4;
+    let width = series.len() * column_width;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width, height
+    );
+    for (day, fish_ages) in series.iter().enumerate() {
+        for (age, (start, end)) in stack(fish_ages, height).iter().enumerate() {
+            if start == end {
+                continue;
+            }
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>day {} age {}: {}</title></rect>\n",
+                day * column_width,
+                start,
+                column_width,
+                end - start,
+                AGE_COLOURS[age],
+                day,
+                age,
+                fish_ages[age]
+            ));
+        }
+    }
+    // growth ratios between 1 and 2 from the bottom to the top
+    let points: Vec<String> = growth_ratios(series)
+        .iter()
+        .enumerate()
+        .filter_map(|(day, growth)| {
+            growth.map(|growth| {
+                let y = height as f64 * (2.0 - growth.clamp(1.0, 2.0));
+                format!("{},{:.1}", day * column_width + column_width / 2, y)
+            })
+        })
+        .collect();
+    if !points.is_empty() {
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"black\"/>\n",
+            points.join(" ")
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+type TransitionMatrix<T> = [[T; 9]; 9];
+
+/// The matrix that gives the fish ages of the next day when multiplied with
+/// the fish ages of a day, column i is what a single fish of age i becomes.
+fn transition_matrix<T: From<u64>>() -> TransitionMatrix<T> {
+    let mut matrix = [[0; 9]; 9];
+    for age in 0..9 {
+        let mut fish_ages = [0; 9];
+        fish_ages[age] = 1;
+        for (row, amount) in simulate_day(fish_ages).iter().enumerate() {
+            matrix[row][age] = *amount;
+        }
+    }
+    matrix.map(|row| row.map(T::from))
+}
+
+/// Any count of fish that the projection can be done in, a u64 or u128 will
+/// give None when the fish don't fit anymore, a BigUint always counts exactly.
+trait FishCount: Clone + From<u64> + Zero + CheckedAdd + CheckedMul {}
+impl<T: Clone + From<u64> + Zero + CheckedAdd + CheckedMul> FishCount for T {}
+
+/// Project the fish ages after the amount of days by raising the transition
+/// matrix to that power, squaring it for every bit of the days. None when
+/// the amounts don't fit in the count.
+fn project<T: FishCount>(fish_ages: [T; 9], days: u64) -> Option<[T; 9]> {
+    let mut fish_ages = fish_ages;
+    let mut matrix = transition_matrix();
+    let mut days = days;
+    while days > 0 {
+        if days & 1 == 1 {
+            fish_ages = multiply_ages(&matrix, &fish_ages)?;
+        }
+        days >>= 1;
+        if days > 0 {
+            matrix = multiply_matrices(&matrix, &matrix)?;
+        }
+    }
+    Some(fish_ages)
+}
+
+/// Project the fish ages after the amount of days without ever overflowing.
+fn project_exact(fish_ages: FishAges, days: u64) -> [BigUint; 9] {
+    project(fish_ages.map(BigUint::from), days).expect("Expected a BigUint to never overflow")
+}
+
+/// The most days a projection that doesn't fit in a u128 is counted exactly
+/// for without asking, the population grows by about a bit every 8 days.
+const EXACT_PROJECTION_DAYS: u64 = 100_000;
+
+/// The population after the amount of days, counted in a u128 while it fits
+/// and exactly otherwise. Counting exactly gets slow for very long
+/// projections, so beyond EXACT_PROJECTION_DAYS that's only done when asked.
+fn project_population(fish_ages: FishAges, days: u64, exact: bool) -> Option<BigUint> {
+    let population = project(fish_ages.map(u128::from), days).and_then(|fish_ages| {
+        fish_ages
+            .iter()
+            .try_fold(0u128, |sum, &n| sum.checked_add(n))
+    });
+    match population {
+        Some(population) => Some(population.into()),
+        None => (exact || days <= EXACT_PROJECTION_DAYS)
+            .then(|| project_exact(fish_ages, days).iter().sum()),
+    }
+}
+
+/// Find the first day the population grows past the count. The transition
+/// matrix is squared until a power of two days is past it, then those powers
+/// are taken from large to small whenever they stay at or below the count,
+/// which works as the lanternfish never die. None when there are no fish.
+fn first_day_exceeding(fish_ages: FishAges, count: &BigUint) -> Option<u64> {
+    let fish_ages = fish_ages.map(BigUint::from);
+    let total = |fish_ages: &[BigUint; 9]| fish_ages.iter().sum::<BigUint>();
+    if &total(&fish_ages) > count {
+        return Some(0);
+    }
+    if total(&fish_ages).is_zero() {
+        return None;
+    }
+
+    // the transition matrices for 1, 2, 4, .. days until past the count
+    let mut powers: Vec<TransitionMatrix<BigUint>> = vec![transition_matrix()];
+    loop {
+        let last = powers.last().unwrap();
+        if &total(&multiply_exact(last, &fish_ages)) > count {
+            break;
+        }
+        let squared = multiply_matrices(last, last).expect("Expected a BigUint to never overflow");
+        powers.push(squared);
+    }
+
+    // take the largest steps that don't pass the count
+    let mut day = 0;
+    let mut fish_ages = fish_ages;
+    for (power, matrix) in powers.iter().enumerate().rev() {
+        let next = multiply_exact(matrix, &fish_ages);
+        if &total(&next) <= count {
+            fish_ages = next;
+            day += 1 << power;
+        }
+    }
+    Some(day + 1)
+}
+
+fn multiply_exact(matrix: &TransitionMatrix<BigUint>, fish_ages: &[BigUint; 9]) -> [BigUint; 9] {
+    multiply_ages(matrix, fish_ages).expect("Expected a BigUint to never overflow")
+}
+
+fn multiply_matrices<T: FishCount>(
+    a: &TransitionMatrix<T>,
+    b: &TransitionMatrix<T>,
+) -> Option<TransitionMatrix<T>> {
+    let mut product: TransitionMatrix<T> = [(); 9].map(|_| [(); 9].map(|_| T::zero()));
+    for (row, product_row) in product.iter_mut().enumerate() {
+        for (column, cell) in product_row.iter_mut().enumerate() {
+            *cell = (0..9).try_fold(T::zero(), |sum, i| {
+                sum.checked_add(&a[row][i].checked_mul(&b[i][column])?)
+            })?;
+        }
+    }
+    Some(product)
+}
+
+fn multiply_ages<T: FishCount>(matrix: &TransitionMatrix<T>, fish_ages: &[T; 9]) -> Option<[T; 9]> {
+    let mut product: [T; 9] = [(); 9].map(|_| T::zero());
+    for (row, cell) in product.iter_mut().enumerate() {
+        *cell = (0..9).try_fold(T::zero(), |sum, i| {
+            sum.checked_add(&matrix[row][i].checked_mul(&fish_ages[i])?)
+        })?;
+    }
+    Some(product)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     #[test]
     fn test_load_fish_ages() {
@@ -205,4 +834,426 @@ mod tests {
 
         assert_eq!(fish_ages.iter().sum::<u64>(), 5934);
     }
+
+    #[test]
+    fn test_transition_matrix() {
+        let matrix: TransitionMatrix<u64> = transition_matrix();
+
+        // a fish of age 0 becomes a fish of age 6 and one of age 8
+        assert_eq!(
+            matrix.iter().map(|row| row[0]).collect::<Vec<_>>(),
+            vec![0, 0, 0, 0, 0, 0, 1, 0, 1]
+        );
+        // every other fish ages a day
+        for age in 1..9 {
+            assert_eq!(matrix[age - 1][age], 1);
+            assert_eq!(matrix.iter().map(|row| row[age]).sum::<u64>(), 1);
+        }
+    }
+
+    #[test]
+    fn test_project_matches_simulation() {
+        // Initial state: 3,4,3,1,2
+        let initial_fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        let mut fish_ages = initial_fish_ages;
+        for day in 0..=256 {
+            assert_eq!(project(initial_fish_ages, day), Some(fish_ages));
+            fish_ages = simulate_day(fish_ages);
+        }
+    }
+
+    #[test]
+    fn test_project_256_days() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        assert_eq!(
+            project(fish_ages, 256).map(|fish_ages| fish_ages.iter().sum::<u64>()),
+            Some(26984457539)
+        );
+    }
+
+    #[test]
+    fn test_project_overflow() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages: FishAges = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        assert_eq!(project(fish_ages, 1_000_000_000_000), None);
+    }
+
+    #[test]
+    fn test_project_u128_overflow() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0u128, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        // a u128 holds more days than a u64, but still runs out
+        assert!(project(fish_ages.map(|amount| amount as u64), 700).is_none());
+        assert!(project(fish_ages, 700).is_some());
+        assert_eq!(project(fish_ages, 1200), None);
+    }
+
+    #[test]
+    fn test_project_population() {
+        let fish_ages = load_fish_ages("3,4,3,1,2\n");
+        let exact_total = |days| project_exact(fish_ages, days).iter().sum::<BigUint>();
+
+        assert_eq!(
+            project_population(fish_ages, 256, false),
+            Some(BigUint::from(26984457539u64))
+        );
+        // past a u128, but still short enough to count exactly
+        assert_eq!(
+            project_population(fish_ages, 2000, false),
+            Some(exact_total(2000))
+        );
+        // far too long to count exactly without asking
+        assert_eq!(
+            project_population(fish_ages, 1_000_000_000_000, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_project_exact_matches_u64() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        for days in [0, 1, 18, 80, 256, 300] {
+            assert_eq!(
+                project_exact(fish_ages, days),
+                project(fish_ages, days).unwrap().map(BigUint::from)
+            );
+        }
+    }
+
+    #[test]
+    fn test_project_exact_beyond_u128() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        // every day after the fish grow, it's the sum of the fish 7 and 9 days
+        // before, which holds for the exact counts far past any overflow
+        let total = |days| project_exact(fish_ages, days).iter().sum::<BigUint>();
+        let days = 5000;
+        assert_eq!(total(days), total(days - 7) + total(days - 9));
+        assert!(total(days) > BigUint::from(u128::MAX));
+    }
+
+    /// Simulate every fish on its own as (timer, age), the way the puzzle
+    /// describes it, to check the models against.
+    fn simulate_fish_one_by_one(species: &Species, timers: &[usize], days: usize) -> usize {
+        let newborn_timer = species.newborn_timer();
+        let mut fish: Vec<(usize, usize)> = timers
+            .iter()
+            .map(|&timer| (timer, newborn_timer - timer))
+            .filter(|&(_, age)| species.lifespan.is_none_or(|lifespan| age < lifespan))
+            .collect();
+        for _ in 0..days {
+            let mut newborns = 0;
+            for (timer, age) in fish.iter_mut() {
+                if *timer == 0 {
+                    *timer = species.cycle - 1;
+                    newborns += 1;
+                } else {
+                    *timer -= 1;
+                }
+                *age += 1;
+            }
+            fish.retain(|&(_, age)| species.lifespan.is_none_or(|lifespan| age < lifespan));
+            fish.extend((0..newborns).map(|_| (newborn_timer, 0)));
+        }
+        fish.len()
+    }
+
+    #[test_case("7/2" => Some(LANTERNFISH) ; "lanternfish")]
+    #[test_case("5/1/30" => Some(Species { cycle: 5, newborn_delay: 1, lifespan: Some(30) }) ; "mortal")]
+    #[test_case("0/2" => None ; "no cycle")]
+    #[test_case("7" => None ; "missing delay")]
+    #[test_case("7/2/x" => None ; "invalid lifespan")]
+    #[test_case("7/2/30/1" => None ; "too many numbers")]
+    fn test_species_parse(description: &str) -> Option<Species> {
+        Species::parse(description)
+    }
+
+    #[test]
+    fn test_lanternfish_lifecycle() {
+        let lifecycle = LANTERNFISH.lifecycle();
+
+        assert_eq!(
+            lifecycle.next,
+            [6, 0, 1, 2, 3, 4, 5, 6, 7].map(Some).to_vec()
+        );
+        assert_eq!(lifecycle.spawns.iter().filter(|&&spawns| spawns).count(), 1);
+        assert!(lifecycle.spawns[0]);
+        assert_eq!(lifecycle.newborn, 8);
+    }
+
+    #[test_case(LANTERNFISH ; "lanternfish")]
+    #[test_case(Species { cycle: 7, newborn_delay: 2, lifespan: Some(1000) } ; "long lived lanternfish")]
+    #[test_case(Species { cycle: 7, newborn_delay: 2, lifespan: Some(20) } ; "short lived lanternfish")]
+    #[test_case(Species { cycle: 4, newborn_delay: 1, lifespan: None } ; "fast")]
+    #[test_case(Species { cycle: 5, newborn_delay: 3, lifespan: Some(12) } ; "slow and mortal")]
+    #[test_case(Species { cycle: 3, newborn_delay: 4, lifespan: Some(5) } ; "dies after first spawn")]
+    fn test_model_matches_fish_one_by_one(species: Species) {
+        // Initial state: 3,4,3,1,2
+        let timers = [3, 4, 3, 1, 2];
+
+        let model = Model::new(vec![species.clone()]);
+        let mut populations = model.populate(&load_fish_ages("3,4,3,1,2")).unwrap();
+        for day in 0..=60 {
+            assert_eq!(
+                populations[0].iter().sum::<u64>() as usize,
+                simulate_fish_one_by_one(&species, &timers, day),
+                "day {}",
+                day
+            );
+            populations = model.simulate_day(populations).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_model_multiple_species() {
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+        let mortal = Species {
+            cycle: 7,
+            newborn_delay: 2,
+            lifespan: Some(10),
+        };
+        let model = Model::new(vec![LANTERNFISH, mortal]);
+
+        let mut populations = model.populate(&fish_ages).unwrap();
+        for _ in 0..80 {
+            populations = model.simulate_day(populations).unwrap();
+        }
+
+        // the lanternfish are unaffected by the other species
+        assert_eq!(populations[0].iter().sum::<u64>(), 5934);
+        // fish that die at 10 days only ever create one fish, at 8 days old,
+        // so they just replace themselves
+        assert_eq!(populations[1].iter().sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn test_model_timer_too_long() {
+        // fish of 2/1 start at a timer of 2, so there can't be any at 3
+        let species = Species::parse("2/1").unwrap();
+        let model = Model::new(vec![LANTERNFISH, species.clone()]);
+
+        assert_eq!(
+            model.populate(&load_fish_ages("3,4,3,1,2")),
+            Err(SimulationError::TimerTooLong { timer: 3, species })
+        );
+        assert!(model.populate(&load_fish_ages("1,2,1")).is_ok());
+    }
+
+    #[test]
+    fn test_model_overflow() {
+        // every fish creates a new one every day, doubling them every day
+        let model = Model::new(vec![Species::parse("1/0").unwrap()]);
+
+        let mut populations = model.populate(&load_fish_ages("0")).unwrap();
+        for _ in 0..63 {
+            populations = model.simulate_day(populations).unwrap();
+        }
+
+        assert_eq!(populations[0].iter().sum::<u64>(), 1 << 63);
+        assert_eq!(
+            model.simulate_day(populations),
+            Err(SimulationError::Overflow)
+        );
+    }
+
+    #[test_case(vec![1, 2, 3] => Ok(6) ; "fits")]
+    #[test_case(vec![u64::MAX, 0] => Ok(u64::MAX) ; "just fits")]
+    #[test_case(vec![u64::MAX, 1] => Err(SimulationError::Overflow) ; "too many")]
+    fn test_count_population(population: Population) -> Result<u64, SimulationError> {
+        count_population(&population)
+    }
+
+    #[test]
+    fn test_population_series() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        let series = population_series(fish_ages, 18).unwrap();
+
+        assert_eq!(series.len(), 19);
+        assert_eq!(series[0], fish_ages);
+        assert_eq!(series[2], [1, 2, 1, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(series[18].iter().sum::<u64>(), 26);
+    }
+
+    #[test]
+    fn test_population_series_overflow() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+        let fits = |days| {
+            project(fish_ages, days).is_some_and(|fish_ages| {
+                fish_ages
+                    .iter()
+                    .try_fold(0u64, |total, &amount| total.checked_add(amount))
+                    .is_some()
+            })
+        };
+        let last_day = (0..).take_while(|&days| fits(days)).last().unwrap() as usize;
+
+        assert_eq!(
+            population_series(fish_ages, last_day).map(|series| series.len()),
+            Some(last_day + 1)
+        );
+        assert_eq!(population_series(fish_ages, last_day + 1), None);
+        assert_eq!(
+            population_series([u64::MAX, 1, 0, 0, 0, 0, 0, 0, 0], 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_growth_ratios() {
+        // Initial state: 3,4,3,1,2
+        let series = population_series([0, 1, 1, 2, 1, 0, 0, 0, 0], 256).unwrap();
+
+        let ratios = growth_ratios(&series);
+
+        assert_eq!(ratios.len(), series.len());
+        assert_eq!(ratios[0], None);
+        // no new fish on the first day
+        assert_eq!(ratios[1], Some(1.0));
+        // after 6 more fish out of 5
+        assert_eq!(ratios[2], Some(6.0 / 5.0));
+        // it slowly stabilises at the root of x^9 = x^2 + 1
+        let root = 1.0910244704807566;
+        let distance = |day: usize| (ratios[day].unwrap() - root).abs();
+        assert!(distance(256) < distance(80));
+        assert!(distance(256) < 0.005);
+    }
+
+    #[test]
+    fn test_growth_ratios_no_fish() {
+        let series = population_series([0; 9], 2).unwrap();
+
+        assert_eq!(growth_ratios(&series), vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_render_csv() {
+        // Initial state: 3,4,3,1,2
+        let series = population_series([0, 1, 1, 2, 1, 0, 0, 0, 0], 2).unwrap();
+
+        let expected_csv = "\
+            day,age_0,age_1,age_2,age_3,age_4,age_5,age_6,age_7,age_8,total,growth\n\
+            0,0,1,1,2,1,0,0,0,0,5,\n\
+            1,1,1,2,1,0,0,0,0,0,5,1.000000\n\
+            2,1,2,1,0,0,0,1,0,1,6,1.200000\n";
+
+        assert_eq!(render_csv(&series), expected_csv);
+    }
+
+    #[test]
+    fn test_render_json() {
+        // Initial state: 3,4,3,1,2
+        let series = population_series([0, 1, 1, 2, 1, 0, 0, 0, 0], 1).unwrap();
+
+        assert_eq!(
+            render_json(&series),
+            "[{\"day\":0,\"ages\":[0,1,1,2,1,0,0,0,0],\"total\":5,\"growth\":null},\
+            {\"day\":1,\"ages\":[1,1,2,1,0,0,0,0,0],\"total\":5,\"growth\":1.000000}]"
+        );
+    }
+
+    #[test]
+    fn test_stack() {
+        let fish_ages = [1, 0, 2, 0, 0, 0, 0, 0, 1];
+
+        let stack = stack(&fish_ages, 8);
+
+        assert_eq!(stack[0], (0, 2));
+        assert_eq!(stack[1], (2, 2));
+        assert_eq!(stack[2], (2, 6));
+        assert_eq!(stack[7], (6, 6));
+        assert_eq!(stack[8], (6, 8));
+    }
+
+    #[test]
+    fn test_render_chart() {
+        // Initial state: 3,4,3,1,2
+        let series = population_series([0, 1, 1, 2, 1, 0, 0, 0, 0], 2).unwrap();
+
+        let expected_chart = "   \
+               0 |1122333344| 5\n   \
+               1 |0011222233| 5 x1.000\n   \
+               2 |0011122688| 6 x1.200\n";
+
+        assert_eq!(render_chart(&series, 10), expected_chart);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        // Initial state: 3,4,3,1,2
+        let series = population_series([0, 1, 1, 2, 1, 0, 0, 0, 0], 2).unwrap();
+
+        let svg = render_svg(&series);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        // the ages that have fish on every day
+        assert_eq!(svg.matches("<rect").count(), 4 + 4 + 5);
+        assert!(svg.contains("<title>day 2 age 8: 1</title>"));
+        assert_eq!(svg.matches("<polyline").count(), 1);
+    }
+
+    #[test_case(0 => Some(0) ; "any fish")]
+    #[test_case(4 => Some(0) ; "already more")]
+    #[test_case(5 => Some(2) ; "first new fish")]
+    #[test_case(25 => Some(18) ; "just before 18 days")]
+    #[test_case(26 => Some(19) ; "at 18 days")]
+    #[test_case(5933 => Some(80) ; "just before 80 days")]
+    #[test_case(5934 => Some(81) ; "at 80 days")]
+    #[test_case(26984457538 => Some(256) ; "just before 256 days")]
+    fn test_first_day_exceeding(count: u64) -> Option<u64> {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        first_day_exceeding(fish_ages, &BigUint::from(count))
+    }
+
+    #[test]
+    fn test_first_day_exceeding_matches_simulation() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+
+        let totals: Vec<u64> = population_series(fish_ages, 300)
+            .unwrap()
+            .iter()
+            .map(|fish_ages| fish_ages.iter().sum())
+            .collect();
+        for count in (0..300).chain((1..25).map(|power| 3u64.pow(power))) {
+            let expected_day = totals.iter().position(|&total| total > count);
+            assert_eq!(
+                first_day_exceeding(fish_ages, &BigUint::from(count)),
+                expected_day.map(|day| day as u64),
+                "count {}",
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_day_exceeding_huge_count() {
+        // Initial state: 3,4,3,1,2
+        let fish_ages = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+        let count = BigUint::from(10u32).pow(100);
+
+        let day = first_day_exceeding(fish_ages, &count).unwrap();
+
+        let total = |days| project_exact(fish_ages, days).iter().sum::<BigUint>();
+        assert!(total(day) > count);
+        assert!(total(day - 1) <= count);
+    }
+
+    #[test]
+    fn test_first_day_exceeding_no_fish() {
+        assert_eq!(first_day_exceeding([0; 9], &BigUint::from(1u32)), None);
+    }
 }
@@ -83,57 +83,769 @@
 /// they spend to align to that position?
 use itertools::Itertools;
 use std::collections::HashMap;
+use std::fs;
 
 const INPUT: &str = include_str!("../input/day_07");
 
-pub fn run() {
+pub fn run(options: &[&str]) {
     let crabs = load_crabs(INPUT);
 
+    let (position, fuel_cost) = Linear
+        .optimise(&crabs)
+        .expect("Expected to find a minimal fuel cost");
     println!(
-        "The least fuel for the crabs to spend to align is: {}",
-        find_least_fuel_cost(&crabs, calculate_fuel_cost)
+        "The least fuel for the crabs to spend to align is: {} (at position {})",
+        fuel_cost, position
     );
 
+    let (position, fuel_cost) = Triangular
+        .optimise(&crabs)
+        .expect("Expected to find a minimal fuel cost");
     println!(
-        "The least fuel for the crabs to spend with the expensive fuel costs to align is: {}",
-        find_least_fuel_cost(&crabs, calculate_expensive_fuel_cost)
+        "The least fuel for the crabs to spend with the expensive fuel costs to align is: {} (at position {})",
+        fuel_cost, position
     );
+
+    let mut models: Vec<Box<dyn FuelModel>> = vec![];
+    let mut brute_force = false;
+    let mut reports = vec![];
+    let mut spatial = vec![];
+    for &option in options {
+        match option.split_once('=').unwrap_or((option, "")) {
+            // other ways for crabs to burn fuel
+            ("quadratic", "") => models.push(Box::new(Quadratic)),
+            ("capped", cap) => models.push(Box::new(Capped(
+                cap.parse()
+                    .ok()
+                    .filter(|&cap| cap >= 0)
+                    .unwrap_or_else(|| panic!("Invalid cap: {}", cap)),
+            ))),
+            ("steps", steps) => models.push(Box::new(
+                StepTable::parse(steps).unwrap_or_else(|| panic!("Invalid step costs: {}", steps)),
+            )),
+            // check against trying every position
+            ("brute-force", "") => brute_force = true,
+            // the cost at every position
+            ("table", "") => reports.push(CurveReport::Table),
+            ("curve", "") => reports.push(CurveReport::Ascii),
+            ("svg", path) => reports.push(CurveReport::Svg(path)),
+            // align crabs from a file on more axes
+            ("spatial", path) => spatial.push(path),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    for model in &models {
+        match model.optimise(&crabs) {
+            Some((position, fuel_cost)) => println!(
+                "The least fuel for the crabs to spend with {} fuel costs to align is: {} (at position {})",
+                model.name(),
+                fuel_cost,
+                position
+            ),
+            None => println!("The crabs can't align with {} fuel costs", model.name()),
+        }
+    }
+
+    let all_models: Vec<&dyn FuelModel> = [&Linear as &dyn FuelModel, &Triangular]
+        .into_iter()
+        .chain(models.iter().map(|model| model.as_ref()))
+        .collect();
+
+    if brute_force {
+        for model in &all_models {
+            if let Some((position, fuel_cost)) = model.brute_force(&crabs) {
+                println!(
+                    "Trying every position, the least fuel with {} fuel costs is: {} (at position {})",
+                    model.name(),
+                    fuel_cost,
+                    position
+                );
+            }
+        }
+    }
+
+    for report in reports {
+        match report {
+            CurveReport::Table => print!("{}", render_cost_table(&crabs, &all_models)),
+            CurveReport::Ascii => {
+                for model in &all_models {
+                    println!("{} fuel costs:", model.name());
+                    print!(
+                        "{}",
+                        render_cost_curve(&crabs, *model, CURVE_WIDTH, CURVE_HEIGHT)
+                    );
+                }
+            }
+            CurveReport::Svg(path) => fs::write(path, render_cost_svg(&crabs, &all_models))
+                .unwrap_or_else(|error| panic!("Could not write {}: {}", path, error)),
+        }
+    }
+
+    for path in spatial {
+        let input = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("Could not read {}: {}", path, error));
+        let dimensions = input
+            .split(',')
+            .next()
+            .map_or(0, |crab| crab.split(':').count());
+        match dimensions {
+            1 => print_spatial_alignment::<1>(&input, &all_models),
+            2 => print_spatial_alignment::<2>(&input, &all_models),
+            3 => print_spatial_alignment::<3>(&input, &all_models),
+            _ => panic!("Can only align crabs on 1 to 3 axes, not {}", dimensions),
+        }
+    }
 }
 
 type HorizontalPosition = i32;
 type Amount = i32;
-type FuelCost = i32;
+type FuelCost = i64;
 type Crabs = HashMap<HorizontalPosition, Amount>;
 
-fn find_least_fuel_cost(
-    crabs: &Crabs,
-    calculate_cost: fn(&Crabs, HorizontalPosition) -> FuelCost,
-) -> FuelCost {
-    // simply iterate over all positions to find the smallest
-    (0..*crabs.keys().max().unwrap())
-        .map(|position| calculate_cost(crabs, position))
-        .min()
-        .expect("Expected to find a minimal fuel cost")
+/// How much fuel crabs burn to move, and how to find where they should align.
+trait FuelModel {
+    fn name(&self) -> String;
+
+    /// The fuel for a single crab to move the distance, None when it doesn't
+    /// fit in a FuelCost.
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost>;
+
+    /// Find the position with the least fuel cost, and that cost. The first
+    /// position wins a tie. None when there are no crabs, or every position
+    /// costs too much to count.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        self.brute_force(crabs)
+    }
+
+    /// The fuel for all crabs to align on the position.
+    fn fuel_cost(&self, crabs: &Crabs, position: HorizontalPosition) -> Option<FuelCost> {
+        crabs
+            .iter()
+            .try_fold(0, |total: FuelCost, (&crab_position, &amount)| {
+                let distance = (position as FuelCost - crab_position as FuelCost).abs();
+                total.checked_add(self.cost(distance)?.checked_mul(amount as FuelCost)?)
+            })
+    }
+
+    /// Find the least fuel cost of the positions, skipping the ones that cost
+    /// too much to count.
+    fn least_fuel_cost(
+        &self,
+        crabs: &Crabs,
+        positions: &mut dyn Iterator<Item = HorizontalPosition>,
+    ) -> Option<(HorizontalPosition, FuelCost)> {
+        positions
+            .filter_map(|position| Some((position, self.fuel_cost(crabs, position)?)))
+            .min_by_key(|&(_, cost)| cost)
+    }
+
+    /// Simply try every position between the crabs.
+    fn brute_force(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        let (min, max) = position_range(crabs)?;
+        self.least_fuel_cost(crabs, &mut (min..=max))
+    }
+}
+
+/// Each step costs 1 fuel.
+struct Linear;
+
+impl FuelModel for Linear {
+    fn name(&self) -> String {
+        "linear".to_string()
+    }
+
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost> {
+        Some(distance)
+    }
+
+    /// Moving past a crab makes it cost one more for every crab behind and
+    /// one less for every crab ahead, so the least cost is at the weighted
+    /// median: the first position with at least half the crabs at or before
+    /// it.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        self.least_fuel_cost(crabs, &mut [weighted_median(crabs)?].into_iter())
+    }
+}
+
+/// Each step costs 1 more fuel than the last.
+struct Triangular;
+
+impl FuelModel for Triangular {
+    fn name(&self) -> String {
+        "triangular".to_string()
+    }
+
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost> {
+        // cost will be
+        // 1 + 2 + 3 + .. + n = (n * (n + 1)) / 2
+        // https://en.wikipedia.org/wiki/Triangular_number
+        // halving whichever is even first, so only the result has to fit
+        if distance % 2 == 0 {
+            (distance / 2).checked_mul(distance + 1)
+        } else {
+            distance.checked_mul((distance + 1) / 2)
+        }
+    }
+
+    /// The cost for a crab is half its squared distance plus half its
+    /// distance. The squares pull the best position to the mean and the
+    /// distances can move it at most half a step from there, so only the
+    /// positions around the mean need to be tried.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        self.least_fuel_cost(crabs, &mut around_mean(crabs)?)
+    }
+}
+
+/// Each step costs 2 more fuel than the last, the distance squared.
+struct Quadratic;
+
+impl FuelModel for Quadratic {
+    fn name(&self) -> String {
+        "quadratic".to_string()
+    }
+
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost> {
+        distance.checked_mul(distance)
+    }
+
+    /// The sum of the squared distances is the least at the mean.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        self.least_fuel_cost(crabs, &mut around_mean(crabs)?)
+    }
+}
+
+/// Each step costs 1 fuel, up to a maximum for any distance.
+struct Capped(FuelCost);
+
+impl FuelModel for Capped {
+    fn name(&self) -> String {
+        format!("capped at {}", self.0)
+    }
+
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost> {
+        Some(distance.min(self.0))
+    }
+
+    /// The cost only bends at a crab or the cap away from one, and is a
+    /// straight line between those, so one of them must be the least.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        let (min, max) = position_range(crabs)?;
+        let cap = self.0.min(max as FuelCost - min as FuelCost) as HorizontalPosition;
+        let positions = crabs
+            .keys()
+            .flat_map(|&position| {
+                [
+                    position.saturating_sub(cap),
+                    position,
+                    position.saturating_add(cap),
+                ]
+            })
+            .chain([min, max])
+            .map(|position| position.clamp(min, max))
+            .sorted()
+            .dedup();
+        self.least_fuel_cost(crabs, &mut positions.into_iter())
+    }
+}
+
+/// The cost of every step given, with the last one repeating for any steps
+/// after.
+struct StepTable {
+    steps: Vec<FuelCost>,
+    /// the cost of moving each distance up to the amount of steps given
+    totals: Vec<FuelCost>,
+}
+
+impl StepTable {
+    /// None when there are no steps or a step costs less than nothing.
+    fn new(steps: Vec<FuelCost>) -> Option<StepTable> {
+        if steps.is_empty() || steps.iter().any(|&step| step < 0) {
+            return None;
+        }
+        let mut totals: Vec<FuelCost> = vec![0];
+        for step in &steps {
+            totals.push(totals.last().unwrap().checked_add(*step)?);
+        }
+        Some(StepTable { steps, totals })
+    }
+
+    /// Parse the costs as a comma separated list, e.g. 1,2,4,8.
+    fn parse(steps: &str) -> Option<StepTable> {
+        StepTable::new(
+            steps
+                .split(',')
+                .map(|step| step.trim().parse().ok())
+                .collect::<Option<_>>()?,
+        )
+    }
+
+    /// When the steps never get cheaper, the further a crab moves the faster
+    /// the cost rises.
+    fn is_convex(&self) -> bool {
+        self.steps.windows(2).all(|pair| pair[0] <= pair[1])
+    }
+}
+
+impl FuelModel for StepTable {
+    fn name(&self) -> String {
+        format!("{} step", self.steps.iter().join(","))
+    }
+
+    fn cost(&self, distance: FuelCost) -> Option<FuelCost> {
+        let given = self.steps.len() as FuelCost;
+        if distance <= given {
+            return Some(self.totals[distance as usize]);
+        }
+        let last = *self.steps.last().unwrap();
+        self.totals
+            .last()
+            .unwrap()
+            .checked_add((distance - given).checked_mul(last)?)
+    }
+
+    /// With steps that never get cheaper the total cost only goes down and
+    /// then up, so search for the first position where it stops going down.
+    /// Otherwise there's no telling where the least is, and every position
+    /// is tried.
+    fn optimise(&self, crabs: &Crabs) -> Option<(HorizontalPosition, FuelCost)> {
+        if !self.is_convex() {
+            return self.brute_force(crabs);
+        }
+        // costs too large to count are larger than any other cost
+        let cost = |position| {
+            let cost = self.fuel_cost(crabs, position);
+            (cost.is_none(), cost)
+        };
+        let (mut low, mut high) = position_range(crabs)?;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if cost(middle + 1) >= cost(middle) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        self.least_fuel_cost(crabs, &mut [low].into_iter())
+    }
 }
 
-fn calculate_fuel_cost(crabs: &Crabs, position: HorizontalPosition) -> FuelCost {
+fn print_spatial_alignment<const D: usize>(input: &str, models: &[&dyn FuelModel]) {
+    let crabs = load_spatial_crabs::<D>(input)
+        .unwrap_or_else(|| panic!("Expected every crab to have {} coordinates", D));
+
+    for model in models {
+        match align_per_axis(&crabs, *model) {
+            Some((position, fuel_cost)) => println!(
+                "The least fuel for the crabs to align moving along the axes with {} fuel costs is: {} (at position {:?})",
+                model.name(),
+                fuel_cost,
+                position
+            ),
+            None => println!(
+                "The crabs can't align moving along the axes with {} fuel costs",
+                model.name()
+            ),
+        }
+    }
+    // in a straight line every step costs the same, like the linear fuel costs
+    match align_euclidean(&crabs) {
+        Some((position, fuel_cost)) => println!(
+            "The least fuel for the crabs to align moving in a straight line is: {:.3} (at position {:?})",
+            fuel_cost, position
+        ),
+        None => println!("The crabs can't align moving in a straight line"),
+    }
+}
+
+type SpatialPosition<const D: usize> = [HorizontalPosition; D];
+type SpatialCrabs<const D: usize> = HashMap<SpatialPosition<D>, Amount>;
+
+/// Align the crabs when the fuel for moving along each axis is counted on
+/// its own, the Manhattan distance for linear costs. As the axes don't
+/// affect each other, each axis can be optimised on its own.
+fn align_per_axis<const D: usize>(
+    crabs: &SpatialCrabs<D>,
+    model: &dyn FuelModel,
+) -> Option<(SpatialPosition<D>, FuelCost)> {
+    let mut position = [0; D];
+    let mut total: FuelCost = 0;
+    for (axis, axis_position) in position.iter_mut().enumerate() {
+        let mut projected = Crabs::new();
+        for (crab, &amount) in crabs {
+            *projected.entry(crab[axis]).or_insert(0) += amount;
+        }
+        let (best, cost) = model.optimise(&projected)?;
+        *axis_position = best;
+        total = total.checked_add(cost)?;
+    }
+    Some((position, total))
+}
+
+/// The fuel for all crabs to move in a straight line to the position.
+fn euclidean_fuel_cost<const D: usize>(crabs: &SpatialCrabs<D>, position: &[f64; D]) -> f64 {
     crabs
         .iter()
-        .map(|(crab_position, amount)| (position - crab_position).abs() * amount)
+        .map(|(crab, &amount)| amount as f64 * euclidean_distance(crab, position))
         .sum()
 }
 
-fn calculate_expensive_fuel_cost(crabs: &Crabs, position: HorizontalPosition) -> FuelCost {
+fn euclidean_distance<const D: usize>(crab: &SpatialPosition<D>, position: &[f64; D]) -> f64 {
+    crab.iter()
+        .zip(position)
+        .map(|(&a, b)| (a as f64 - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// How close the iterations need to get before the Euclidean position is
+/// taken as found.
+const EUCLIDEAN_TOLERANCE: f64 = 1e-9;
+const EUCLIDEAN_MAX_ITERATIONS: usize = 100_000;
+
+/// Align the crabs when they move in a straight line, which has no closed
+/// form. Weiszfeld's algorithm starts at the mean and moves to the average
+/// of the crabs weighted by how close they are, until it stops moving. When
+/// it lands on a crab it stays if the pull of the others isn't stronger than
+/// the crabs there, otherwise it steps off in the direction of that pull.
+/// https://en.wikipedia.org/wiki/Geometric_median
+fn align_euclidean<const D: usize>(crabs: &SpatialCrabs<D>) -> Option<([f64; D], f64)> {
+    let total: f64 = crabs.values().map(|&amount| amount as f64).sum();
+    if crabs.is_empty() || total <= 0.0 {
+        return None;
+    }
+    let mut position = [0.0; D];
+    for (crab, &amount) in crabs {
+        for (axis, coordinate) in position.iter_mut().enumerate() {
+            *coordinate += crab[axis] as f64 * amount as f64 / total;
+        }
+    }
+
+    for _ in 0..EUCLIDEAN_MAX_ITERATIONS {
+        let mut weighted_sum = [0.0; D];
+        let mut weights = 0.0;
+        let mut on_crab = 0.0;
+        for (crab, &amount) in crabs {
+            let distance = euclidean_distance(crab, &position);
+            if distance < EUCLIDEAN_TOLERANCE {
+                on_crab += amount as f64;
+                continue;
+            }
+            for (axis, sum) in weighted_sum.iter_mut().enumerate() {
+                *sum += amount as f64 * crab[axis] as f64 / distance;
+            }
+            weights += amount as f64 / distance;
+        }
+        if weights == 0.0 {
+            // every crab is right here
+            break;
+        }
+        let target = weighted_sum.map(|sum| sum / weights);
+
+        let next = if on_crab > 0.0 {
+            // the pull of the other crabs away from the one we're on
+            let pull = position
+                .iter()
+                .zip(&target)
+                .map(|(from, to)| ((to - from) * weights).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            if pull <= on_crab {
+                break;
+            }
+            let stay = on_crab / pull;
+            let mut next = [0.0; D];
+            for (axis, coordinate) in next.iter_mut().enumerate() {
+                *coordinate = (1.0 - stay) * target[axis] + stay * position[axis];
+            }
+            next
+        } else {
+            target
+        };
+
+        let moved = position
+            .iter()
+            .zip(&next)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        position = next;
+        if moved < EUCLIDEAN_TOLERANCE {
+            break;
+        }
+    }
+
+    // it only creeps towards a crab it should end on, so try the closest
+    let cost = euclidean_fuel_cost(crabs, &position);
+    let closest = crabs
+        .keys()
+        .map(|crab| crab.map(|coordinate| coordinate as f64))
+        .min_by(|a, b| {
+            let distance = |crab: &[f64; D]| {
+                crab.iter()
+                    .zip(&position)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f64>()
+            };
+            distance(a).total_cmp(&distance(b))
+        })?;
+    let closest_cost = euclidean_fuel_cost(crabs, &closest);
+    if closest_cost < cost {
+        return Some((closest, closest_cost));
+    }
+    Some((position, cost))
+}
+
+/// The first position with at least half the crabs at or before it.
+fn weighted_median(crabs: &Crabs) -> Option<HorizontalPosition> {
+    let total: FuelCost = crabs.values().map(|&amount| amount as FuelCost).sum();
+    let mut passed = 0;
     crabs
         .iter()
-        .map(|(crab_position, amount)| {
-            let distance = (position - crab_position).abs();
-            // cost will be
-            // 1 + 2 + 3 + .. + n = (n * (n + 1)) / 2
-            // https://en.wikipedia.org/wiki/Triangular_number
-            ((distance * (distance + 1)) / 2) * amount
+        .sorted()
+        .find(|(_, &amount)| {
+            passed += amount as FuelCost;
+            passed * 2 >= total
         })
-        .sum()
+        .map(|(&position, _)| position)
+}
+
+fn mean(crabs: &Crabs) -> Option<f64> {
+    let total: f64 = crabs.values().map(|&amount| amount as f64).sum();
+    let sum: f64 = crabs
+        .iter()
+        .map(|(&position, &amount)| position as f64 * amount as f64)
+        .sum();
+    (total > 0.0).then(|| sum / total)
+}
+
+enum CurveReport<'a> {
+    Table,
+    Ascii,
+    Svg(&'a str),
+}
+
+/// The fuel cost at every position between the crabs, None where it's too
+/// large to count.
+fn cost_curve(crabs: &Crabs, model: &dyn FuelModel) -> Vec<(HorizontalPosition, Option<FuelCost>)> {
+    match position_range(crabs) {
+        Some((min, max)) => (min..=max)
+            .map(|position| (position, model.fuel_cost(crabs, position)))
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn render_cost_table(crabs: &Crabs, models: &[&dyn FuelModel]) -> String {
+    let curves: Vec<_> = models
+        .iter()
+        .map(|model| cost_curve(crabs, *model))
+        .collect();
+    let mut table = format!(
+        "position,{}\n",
+        models.iter().map(|model| model.name()).join(",")
+    );
+    for (row, (position, _)) in curves.first().into_iter().flatten().enumerate() {
+        table.push_str(&position.to_string());
+        for curve in &curves {
+            table.push(',');
+            if let Some(cost) = curve[row].1 {
+                table.push_str(&cost.to_string());
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+/// The size of the ASCII cost curves.
+const CURVE_WIDTH: usize = 72;
+const CURVE_HEIGHT: usize = 16;
+
+/// Plot the cost curve with a column for every few positions, showing the
+/// least cost among them. Under it the optimum is marked with an O, the
+/// median with an M and the mean with an A, or an * where they overlap.
+fn render_cost_curve(crabs: &Crabs, model: &dyn FuelModel, width: usize, height: usize) -> String {
+    let curve = cost_curve(crabs, model);
+    if curve.is_empty() || width == 0 || height == 0 {
+        return String::new();
+    }
+    let positions = curve.len();
+    let width = width.min(positions);
+    let column_of = |index: usize| index * width / positions;
+
+    let mut columns: Vec<Option<FuelCost>> = vec![None; width];
+    for (index, (_, cost)) in curve.iter().enumerate() {
+        let column = &mut columns[column_of(index)];
+        *column = match (*column, *cost) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+    let (lowest, highest) = columns
+        .iter()
+        .flatten()
+        .minmax()
+        .into_option()
+        .map_or((0, 0), |(&lowest, &highest)| (lowest, highest));
+    let row_of = |cost: FuelCost| {
+        if highest == lowest {
+            0
+        } else {
+            ((cost - lowest) as f64 / (highest - lowest) as f64 * (height - 1) as f64).round()
+                as usize
+        }
+    };
+
+    let min = curve[0].0;
+    let mut plot = String::new();
+    for row in (0..height).rev() {
+        let label = match row {
+            _ if row == height - 1 => highest.to_string(),
+            0 => lowest.to_string(),
+            _ => String::new(),
+        };
+        plot.push_str(&format!("{:>12} |", label));
+        for cost in &columns {
+            plot.push(match cost {
+                Some(cost) if row_of(*cost) == row => '#',
+                Some(cost) if row_of(*cost) > row => ':',
+                _ => ' ',
+            });
+        }
+        plot.push('\n');
+    }
+
+    let mut markers = vec![' '; width];
+    let mut mark = |position: Option<HorizontalPosition>, marker: char| {
+        if let Some(position) = position {
+            let column = &mut markers[column_of((position - min) as usize)];
+            *column = if *column == ' ' { marker } else { '*' };
+        }
+    };
+    let optimum = model.optimise(crabs);
+    let median = weighted_median(crabs);
+    let mean = mean(crabs);
+    mark(optimum.map(|(position, _)| position), 'O');
+    mark(median, 'M');
+    mark(mean.map(|mean| mean.round() as HorizontalPosition), 'A');
+    plot.push_str(&format!(
+        "{:>12} +{}\n{:>12}  {}\n",
+        "",
+        "-".repeat(width),
+        "",
+        markers.iter().collect::<String>()
+    ));
+    plot.push_str(&format!(
+        "{:>12}  {} to {}, O optimum {}, M median {}, A mean {}\n",
+        "",
+        min,
+        curve[positions - 1].0,
+        optimum.map_or("none".to_string(), |(position, cost)| format!(
+            "{} ({})",
+            position, cost
+        )),
+        median.map_or("none".to_string(), |median| median.to_string()),
+        mean.map_or("none".to_string(), |mean| format!("{:.2}", mean)),
+    ));
+    plot
+}
+
+/// The colours of the models in the SVG cost curves.
+const CURVE_COLOURS: [&str; 6] = [
+    "#1b9e77", "#d95f02", "#7570b3", "#e7298a", "#66a61e", "#e6ab02",
+];
+
+/// Plot the cost curves of all models together, each scaled to its own
+/// highest cost as they differ wildly, with the optimum of every model as a
+/// line in its colour and dashed lines for the median and mean.
+fn render_cost_svg(crabs: &Crabs, models: &[&dyn FuelModel]) -> String {
+    let (width, height) = (800.0, 400.0);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width, height
+    );
+    let (min, max) = match position_range(crabs) {
+        Some(range) => range,
+        None => return svg + "</svg>\n",
+    };
+    let span = (max - min).max(1) as f64;
+    let x_of = |position: f64| (position - min as f64) / span * width;
+
+    for (index, model) in models.iter().enumerate() {
+        let colour = CURVE_COLOURS[index % CURVE_COLOURS.len()];
+        let curve = cost_curve(crabs, *model);
+        let highest = curve
+            .iter()
+            .filter_map(|(_, cost)| *cost)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let points = curve
+            .iter()
+            .filter_map(|(position, cost)| {
+                cost.map(|cost| {
+                    format!(
+                        "{:.1},{:.1}",
+                        x_of(*position as f64),
+                        height - cost as f64 / highest as f64 * height
+                    )
+                })
+            })
+            .join(" ");
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\"><title>{}</title></polyline>\n",
+            points,
+            colour,
+            model.name()
+        ));
+        if let Some((position, cost)) = model.optimise(crabs) {
+            let x = x_of(position as f64);
+            svg.push_str(&format!(
+                "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{}\" stroke=\"{}\"><title>{} optimum {} ({})</title></line>\n",
+                height,
+                colour,
+                model.name(),
+                position,
+                cost
+            ));
+        }
+    }
+
+    let markers = [
+        ("median", weighted_median(crabs).map(|median| median as f64)),
+        ("mean", mean(crabs)),
+    ];
+    for (name, position) in markers {
+        if let Some(position) = position {
+            let x = x_of(position);
+            svg.push_str(&format!(
+                "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{}\" stroke=\"black\" stroke-dasharray=\"4\"><title>{} {}</title></line>\n",
+                height, name, position
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn position_range(crabs: &Crabs) -> Option<(HorizontalPosition, HorizontalPosition)> {
+    crabs.keys().copied().minmax().into_option()
+}
+
+/// The positions next to the mean of the crabs, that are between the crabs.
+fn around_mean(crabs: &Crabs) -> Option<impl Iterator<Item = HorizontalPosition>> {
+    let (min, max) = position_range(crabs)?;
+    let total: i64 = crabs.values().map(|&amount| amount as i64).sum();
+    let sum: i64 = crabs
+        .iter()
+        .map(|(&position, &amount)| position as i64 * amount as i64)
+        .sum();
+    let mean = sum.div_euclid(total);
+    Some(
+        ((mean - 1).max(min as i64) as HorizontalPosition)
+            ..=((mean + 2).min(max as i64) as HorizontalPosition),
+    )
 }
 
 fn load_crabs(input: &str) -> Crabs {
@@ -148,9 +860,28 @@ fn load_crabs(input: &str) -> Crabs {
         .collect()
 }
 
+/// Load crabs with coordinates separated by colons, e.g. 1:2,3:4 for crabs
+/// on two axes. None when a crab doesn't have exactly D coordinates.
+fn load_spatial_crabs<const D: usize>(input: &str) -> Option<SpatialCrabs<D>> {
+    let mut crabs = SpatialCrabs::new();
+    for crab in input
+        .split(',')
+        .map(str::trim)
+        .filter(|crab| !crab.is_empty())
+    {
+        let coordinates = crab
+            .split(':')
+            .map(|coordinate| coordinate.trim().parse().ok())
+            .collect::<Option<Vec<HorizontalPosition>>>()?;
+        *crabs.entry(coordinates.try_into().ok()?).or_insert(0) += 1;
+    }
+    Some(crabs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_helpers::Lcg;
     use test_case::test_case;
 
     #[test]
@@ -173,7 +904,7 @@ mod tests {
             .into_iter()
             .collect();
 
-        calculate_fuel_cost(&crabs, position)
+        Linear.fuel_cost(&crabs, position).unwrap()
     }
 
     #[test]
@@ -182,7 +913,157 @@ mod tests {
             .into_iter()
             .collect();
 
-        assert_eq!(find_least_fuel_cost(&crabs, calculate_fuel_cost), 37);
+        assert_eq!(Linear.brute_force(&crabs), Some((2, 37)));
+    }
+
+    #[test]
+    fn test_find_least_fuel_position_at_max() {
+        // most crabs are at the furthest position
+        let crabs = [(0, 1), (3, 1), (5, 4)].into_iter().collect();
+
+        assert_eq!(Linear.brute_force(&crabs), Some((5, 7)));
+        assert_eq!(Linear.optimise(&crabs), Some((5, 7)));
+    }
+
+    #[test]
+    fn test_find_least_fuel_cost_linear() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(Linear.optimise(&crabs), Some((2, 37)));
+    }
+
+    #[test]
+    fn test_find_least_fuel_cost_triangular() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(Triangular.optimise(&crabs), Some((5, 168)));
+        assert_eq!(Triangular.brute_force(&crabs), Some((5, 168)));
+    }
+
+    /// Generate crabs spread around the origin, repeatable for the seed.
+    fn generated_crabs(seed: u64, amount: usize, spread: u32) -> Crabs {
+        let mut lcg = Lcg::new(seed);
+        let positions: Vec<HorizontalPosition> = (0..amount)
+            .map(|_| lcg.below(spread) as HorizontalPosition - spread as HorizontalPosition / 2)
+            .collect();
+        positions
+            .into_iter()
+            .counts()
+            .into_iter()
+            .map(|(position, amount)| (position, amount as Amount))
+            .collect()
+    }
+
+    #[test_case(0 => Some(0) ; "no distance")]
+    #[test_case(1 => Some(1) ; "one step")]
+    #[test_case(3 => Some(9) ; "three steps")]
+    #[test_case(3_037_000_500 => None ; "too far to count")]
+    fn test_quadratic_cost(distance: FuelCost) -> Option<FuelCost> {
+        Quadratic.cost(distance)
+    }
+
+    #[test_case(2 => Some(2) ; "under the cap")]
+    #[test_case(5 => Some(5) ; "at the cap")]
+    #[test_case(100 => Some(5) ; "over the cap")]
+    fn test_capped_cost(distance: FuelCost) -> Option<FuelCost> {
+        Capped(5).cost(distance)
+    }
+
+    #[test_case(0 => Some(0) ; "no distance")]
+    #[test_case(2 => Some(3) ; "within the table")]
+    #[test_case(3 => Some(7) ; "end of the table")]
+    #[test_case(5 => Some(15) ; "last step repeats")]
+    #[test_case(FuelCost::MAX => None ; "too far to count")]
+    fn test_step_table_cost(distance: FuelCost) -> Option<FuelCost> {
+        StepTable::new(vec![1, 2, 4]).unwrap().cost(distance)
+    }
+
+    #[test]
+    fn test_step_table_parse() {
+        assert_eq!(
+            StepTable::parse("1, 2,4").map(|table| table.steps),
+            Some(vec![1, 2, 4])
+        );
+        assert!(StepTable::parse("").is_none());
+        assert!(StepTable::parse("1,-2").is_none());
+        assert!(StepTable::parse("1,x").is_none());
+    }
+
+    #[test]
+    fn test_step_table_matches_models() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        // steps of 1 are linear, 1,2,3.. up to the distance is triangular
+        let linear = StepTable::new(vec![1]).unwrap();
+        let triangular = StepTable::new((1..=16).collect()).unwrap();
+
+        assert_eq!(linear.optimise(&crabs), Linear.optimise(&crabs));
+        assert_eq!(triangular.optimise(&crabs), Triangular.optimise(&crabs));
+    }
+
+    #[test]
+    fn test_fuel_cost_overflow() {
+        // the crabs are as far apart as they can be
+        let crabs = [(HorizontalPosition::MIN, 1), (HorizontalPosition::MAX, 1)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(Linear.fuel_cost(&crabs, 0), Some((1 << 32) - 1));
+        assert_eq!(
+            Triangular.fuel_cost(&crabs, HorizontalPosition::MAX),
+            Some(FuelCost::MAX - (1 << 31) + 1)
+        );
+        assert_eq!(Quadratic.fuel_cost(&crabs, HorizontalPosition::MAX), None);
+        // right between the crabs it still fits
+        assert_eq!(
+            Quadratic.optimise(&crabs),
+            Some((-1, FuelCost::MAX - (1 << 32) + 2))
+        );
+    }
+
+    #[test]
+    fn test_fuel_cost_overflow_everywhere() {
+        let crabs = [(-1, Amount::MAX), (1, Amount::MAX)].into_iter().collect();
+        let model = StepTable::new(vec![FuelCost::MAX / 4]).unwrap();
+
+        assert_eq!(model.fuel_cost(&crabs, 0), None);
+        assert_eq!(model.optimise(&crabs), None);
+        assert_eq!(model.brute_force(&crabs), None);
+    }
+
+    #[test]
+    fn test_find_least_fuel_cost_matches_brute_force() {
+        for seed in 0..500 {
+            let amount = 1 + seed as usize % 12;
+            let spread = 1 + (seed as u32 * 7) % 40;
+            let crabs = generated_crabs(seed, amount, spread);
+
+            let models: [&dyn FuelModel; 8] = [
+                &Linear,
+                &Triangular,
+                &Quadratic,
+                &Capped(0),
+                &Capped(3),
+                &Capped(10),
+                &StepTable::new(vec![1, 1, 3, 7]).unwrap(),
+                &StepTable::new(vec![5, 1, 2]).unwrap(),
+            ];
+            for model in models {
+                assert_eq!(
+                    model.optimise(&crabs),
+                    model.brute_force(&crabs),
+                    "{} with crabs {:?}",
+                    model.name(),
+                    crabs
+                );
+            }
+        }
     }
 
     #[test_case(2 => 206 ; "position 2, fuel cost 206")]
@@ -192,6 +1073,239 @@ mod tests {
             .into_iter()
             .collect();
 
-        calculate_expensive_fuel_cost(&crabs, position)
+        Triangular.fuel_cost(&crabs, position).unwrap()
+    }
+
+    #[test]
+    fn test_load_spatial_crabs() {
+        let input = "1:2,3:4, 1:2\n";
+
+        let expected_crabs = [([1, 2], 2), ([3, 4], 1)].into_iter().collect();
+
+        assert_eq!(load_spatial_crabs::<2>(input), Some(expected_crabs));
+        assert_eq!(load_spatial_crabs::<3>(input), None);
+        assert_eq!(load_spatial_crabs::<2>("1:2,3"), None);
+        assert_eq!(load_spatial_crabs::<2>("1:x"), None);
+    }
+
+    #[test]
+    fn test_load_spatial_crabs_one_axis() {
+        let input = "16,1,2,0,4,2,7,1,2,14\n";
+
+        let crabs = load_spatial_crabs::<1>(input).unwrap();
+
+        assert_eq!(
+            crabs,
+            load_crabs(input)
+                .into_iter()
+                .map(|(position, amount)| ([position], amount))
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_align_per_axis() {
+        let crabs = [([0, 0], 1), ([4, 1], 1), ([1, 5], 1)]
+            .into_iter()
+            .collect();
+
+        // the median of 0,4,1 and 0,1,5
+        assert_eq!(align_per_axis(&crabs, &Linear), Some(([1, 1], 4 + 5)));
+    }
+
+    #[test]
+    fn test_align_per_axis_matches_brute_force() {
+        for seed in 0..50 {
+            let xs = generated_crabs(seed, 8, 12);
+            let ys = generated_crabs(seed + 1000, 8, 12);
+            let crabs: SpatialCrabs<2> = xs
+                .keys()
+                .sorted()
+                .zip(ys.keys().sorted().cycle())
+                .map(|(&x, &y)| ([x, y], 1 + x.abs() % 3))
+                .collect();
+
+            let (min_x, max_x) = crabs
+                .keys()
+                .map(|crab| crab[0])
+                .minmax()
+                .into_option()
+                .unwrap();
+            let (min_y, max_y) = crabs
+                .keys()
+                .map(|crab| crab[1])
+                .minmax()
+                .into_option()
+                .unwrap();
+            for model in [&Linear as &dyn FuelModel, &Triangular] {
+                let brute_force = (min_x..=max_x)
+                    .cartesian_product(min_y..=max_y)
+                    .map(|(x, y)| {
+                        crabs
+                            .iter()
+                            .map(|(crab, &amount)| {
+                                (model.cost((crab[0] - x).abs() as FuelCost).unwrap()
+                                    + model.cost((crab[1] - y).abs() as FuelCost).unwrap())
+                                    * amount as FuelCost
+                            })
+                            .sum::<FuelCost>()
+                    })
+                    .min();
+
+                assert_eq!(
+                    align_per_axis(&crabs, model).map(|(_, cost)| cost),
+                    brute_force,
+                    "{} with crabs {:?}",
+                    model.name(),
+                    crabs
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_align_euclidean_one_axis() {
+        let crabs = load_spatial_crabs::<1>("16,1,2,0,4,2,7,1,2,14").unwrap();
+
+        let (position, cost) = align_euclidean(&crabs).unwrap();
+
+        // in a line it's the same as the linear cost
+        assert!((cost - 37.0).abs() < 1e-6);
+        assert!((position[0] - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_align_euclidean_square() {
+        let crabs = [([0, 0], 1), ([2, 0], 1), ([0, 2], 1), ([2, 2], 1)]
+            .into_iter()
+            .collect();
+
+        let (position, cost) = align_euclidean(&crabs).unwrap();
+
+        assert!((position[0] - 1.0).abs() < 1e-6 && (position[1] - 1.0).abs() < 1e-6);
+        assert!((cost - 4.0 * 2f64.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_align_euclidean_on_heavy_crab() {
+        // the crabs at the origin outweigh the pull of the others
+        let crabs = [
+            ([0, 0, 0], 5),
+            ([3, 0, 0], 1),
+            ([0, 4, 0], 1),
+            ([0, 0, 5], 1),
+        ]
+        .into_iter()
+        .collect();
+
+        let (position, cost) = align_euclidean(&crabs).unwrap();
+
+        assert!(position.iter().all(|coordinate| coordinate.abs() < 1e-6));
+        assert!((cost - 12.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_align_euclidean_matches_grid_search() {
+        for seed in 0..20 {
+            let xs = generated_crabs(seed, 6, 20);
+            let ys = generated_crabs(seed + 1000, 6, 20);
+            let crabs: SpatialCrabs<2> = xs
+                .keys()
+                .sorted()
+                .zip(ys.keys().sorted().cycle())
+                .map(|(&x, &y)| ([x, y], 1 + y.abs() % 4))
+                .collect();
+
+            let (_, cost) = align_euclidean(&crabs).unwrap();
+
+            // no point on a fine grid around the crabs does any better
+            let grid_cost = (-40..=40)
+                .cartesian_product(-40..=40)
+                .map(|(x, y)| euclidean_fuel_cost(&crabs, &[x as f64 / 4.0, y as f64 / 4.0]))
+                .fold(f64::INFINITY, f64::min);
+            assert!(cost <= grid_cost + 1e-9, "crabs {:?}", crabs);
+        }
+    }
+
+    #[test]
+    fn test_weighted_median_and_mean() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(weighted_median(&crabs), Some(2));
+        assert_eq!(mean(&crabs), Some(4.9));
+        assert_eq!(weighted_median(&Crabs::new()), None);
+        assert_eq!(mean(&Crabs::new()), None);
+    }
+
+    #[test]
+    fn test_cost_curve() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        let curve = cost_curve(&crabs, &Linear);
+
+        assert_eq!(curve.len(), 17);
+        assert_eq!(curve[1], (1, Some(41)));
+        assert_eq!(curve[2], (2, Some(37)));
+        assert_eq!(curve[10], (10, Some(71)));
+    }
+
+    #[test]
+    fn test_render_cost_table() {
+        let crabs = [(0, 1), (2, 1)].into_iter().collect();
+
+        let expected_table = "\
+            position,linear,triangular\n\
+            0,2,3\n\
+            1,2,2\n\
+            2,2,3\n";
+
+        assert_eq!(
+            render_cost_table(&crabs, &[&Linear, &Triangular]),
+            expected_table
+        );
+    }
+
+    #[test]
+    fn test_render_cost_curve() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        let curve = render_cost_curve(&crabs, &Triangular, 17, 4);
+        let lines: Vec<&str> = curve.lines().collect();
+
+        // a line per row, the axis, the markers and the legend
+        assert_eq!(lines.len(), 4 + 3);
+        assert_eq!(lines[0], "         817 |                #");
+        assert_eq!(lines[1], "             |             ###:");
+        assert_eq!(lines[2], "             |#         ###::::");
+        assert_eq!(lines[3], "         168 |:#########:::::::");
+        // the optimum at 5, the median at 2 and the mean rounded to 5
+        assert_eq!(lines[5], "                M  *           ");
+        assert_eq!(
+            lines[6],
+            "              0 to 16, O optimum 5 (168), M median 2, A mean 4.90"
+        );
+    }
+
+    #[test]
+    fn test_render_cost_svg() {
+        let crabs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)]
+            .into_iter()
+            .collect();
+
+        let svg = render_cost_svg(&crabs, &[&Linear, &Triangular]);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains("<title>linear optimum 2 (37)</title>"));
+        assert!(svg.contains("<title>triangular optimum 5 (168)</title>"));
+        assert!(svg.contains("<title>median 2</title>"));
+        assert!(svg.contains("<title>mean 4.9</title>"));
     }
 }
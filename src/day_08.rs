@@ -167,119 +167,598 @@
 /// four-digit output values. What do you get if you add up all of the output
 /// values?
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
 
 const INPUT: &str = include_str!("../input/day_08");
 
-pub fn run() {
-    let segment_displays = load_segment_displays(INPUT);
+pub fn run(options: &[&str]) {
+    let segment_displays = load_segment_displays(INPUT)
+        .unwrap_or_else(|(line, error)| panic!("Line {} is invalid: {}", line + 1, error));
+
+    let mut font = Font::standard();
+    let mut render = false;
+    for &option in options {
+        match option.split_once('=').unwrap_or((option, "")) {
+            // draw the output values before and after correcting the wiring
+            ("render", "") => render = true,
+            // the symbols the displays show, standard, hex or symbol=segments,..
+            ("font", description) => {
+                font = Font::parse(description)
+                    .unwrap_or_else(|| panic!("Invalid font: {}", description))
+            }
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
 
     println!(
         "Digits 1, 4, 7 or 8 appears {} times in the output values",
-        count_occurance(&segment_displays, vec![1, 4, 7, 8])
+        count_occurance(&segment_displays, &font, &['1', '4', '7', '8'])
     );
 
-    let total_sum = segment_displays
-        .iter()
-        .map(|(examples, displays)| {
-            displays.iter().fold(0, |number, display| {
-                let digit = identify_digit(&examples, display).expect("Could not identify a digit");
-                number * 10 + digit
-            })
-        })
-        .sum::<usize>();
+    // values can only be added up when the symbols are digits
+    let radix = font.radix();
+    let mut total_sum = 0u64;
+    let mut undecoded = 0;
+    for (entry, (examples, displays)) in segment_displays.iter().enumerate() {
+        let diagnosis = diagnose(examples, displays, &font);
+        let value = diagnosis.value();
+        if render {
+            println!("Entry {}:", entry + 1);
+            match &diagnosis.wirings[..] {
+                [wiring] => println!("{}", render_entry(displays, wiring, &font)),
+                // an entry that can't be decoded gets its diagnosis below
+                _ if value.is_some() => println!("{}", diagnosis),
+                _ => {}
+            }
+        }
+        match (value, radix) {
+            (Some(value), Some(radix)) => {
+                match parse_value(&value, radix).and_then(|value| total_sum.checked_add(value)) {
+                    Some(sum) => total_sum = sum,
+                    None => {
+                        println!(
+                            "Value {} of entry {} is too big to add up",
+                            value,
+                            entry + 1
+                        );
+                        undecoded += 1;
+                    }
+                }
+            }
+            (Some(value), None) => println!("Entry {} shows {}", entry + 1, value),
+            (None, _) => {
+                println!("Could not decode entry {}:\n{}", entry + 1, diagnosis);
+                undecoded += 1;
+            }
+        }
+    }
+
+    if radix.is_none() {
+        println!("{} entries could not be decoded", undecoded);
+    } else if undecoded > 0 {
+        println!(
+            "Adding up the output values that could be decoded gives: {} ({} entries could not be decoded)",
+            total_sum, undecoded
+        );
+    } else {
+        println!("Adding up all the output values gives: {}", total_sum);
+    }
+}
 
-    println!("Adding up all the output values gives: {}", total_sum);
+/// The number the digits of a value stand for in the radix, None when it's
+/// too big for a u64.
+fn parse_value(value: &str, radix: u32) -> Option<u64> {
+    value.chars().try_fold(0u64, |number, digit| {
+        number
+            .checked_mul(radix as u64)?
+            .checked_add(digit.to_digit(radix)? as u64)
+    })
 }
 
+/// Count the displays that are one of the symbols. The symbols that have a
+/// unique amount of segments are told apart without solving the wiring, the
+/// wiring is only solved when other symbols are counted as well, then a
+/// display counts when every wiring that fits decodes it to the symbol.
 fn count_occurance(
-    segment_displays: &Vec<(SegmentDisplays, SegmentDisplays)>,
-    digits_to_count: Vec<usize>,
+    segment_displays: &[(SegmentDisplays, SegmentDisplays)],
+    font: &Font,
+    symbols_to_count: &[char],
 ) -> usize {
+    let by_length = |display: &SegmentDisplay| {
+        font.symbols
+            .iter()
+            .filter(|(_, segments)| segments.len() == display.len())
+            .exactly_one()
+            .ok()
+            .map(|(symbol, _)| *symbol)
+    };
+    let needs_wiring = symbols_to_count.iter().any(|symbol| {
+        font.symbols
+            .iter()
+            .find(|(other, _)| other == symbol)
+            .is_some_and(|(_, segments)| by_length(segments).is_none())
+    });
     segment_displays
         .iter()
-        .map(|(examples, displays)| {
+        .map(|(patterns, displays)| {
+            let diagnosis = needs_wiring.then(|| diagnose(patterns, displays, font));
             displays
                 .iter()
-                .filter_map(|display| identify_digit(&examples, display))
-                .filter(|identified_digit| digits_to_count.contains(identified_digit))
+                .enumerate()
+                .filter_map(|(position, display)| {
+                    by_length(display).or_else(|| {
+                        match diagnosis.as_ref()?.possible_symbols[position][..] {
+                            [symbol] => Some(symbol),
+                            _ => None,
+                        }
+                    })
+                })
+                .filter(|symbol| symbols_to_count.contains(symbol))
                 .count()
         })
         .sum()
 }
 
-type SegmentDisplay<'a> = &'a str;
-type SegmentDisplays<'a> = Vec<SegmentDisplay<'a>>;
-
-fn identify_digit<'a>(
-    examples: &SegmentDisplays<'a>,
-    display: &SegmentDisplay<'a>,
-) -> Option<usize> {
-    match display.len() {
-        2 => Some(1),
-        3 => Some(7),
-        4 => Some(4),
-        7 => Some(8),
-        length => {
-            // this is harder to identify
-            let identified_digits: HashMap<_, _> = examples
+/// The segments, or wires, that are on, a bit for each of a to g.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SegmentDisplay(u8);
+type SegmentDisplays = Vec<SegmentDisplay>;
+
+#[derive(Debug, PartialEq)]
+enum SegmentError {
+    InvalidLetter(char),
+    RepeatedLetter(char),
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SegmentError::InvalidLetter(letter) => {
+                write!(f, "'{}' is not a segment, only a to g are", letter)
+            }
+            SegmentError::RepeatedLetter(letter) => {
+                write!(f, "segment {} appears more than once", letter)
+            }
+        }
+    }
+}
+
+impl SegmentDisplay {
+    /// Parse the letters of the segments in any order.
+    fn parse(letters: &str) -> Result<SegmentDisplay, SegmentError> {
+        letters
+            .chars()
+            .try_fold(SegmentDisplay(0), |display, letter| {
+                let bit = match letter {
+                    'a'..='g' => 1 << (letter as u8 - b'a'),
+                    _ => return Err(SegmentError::InvalidLetter(letter)),
+                };
+                if display.0 & bit != 0 {
+                    return Err(SegmentError::RepeatedLetter(letter));
+                }
+                Ok(SegmentDisplay(display.0 | bit))
+            })
+    }
+
+    /// The same as parse, for letters known to be valid.
+    const fn from_letters(letters: &str) -> SegmentDisplay {
+        let letters = letters.as_bytes();
+        let mut segments = 0;
+        let mut i = 0;
+        while i < letters.len() {
+            segments |= 1 << (letters[i] - b'a');
+            i += 1;
+        }
+        SegmentDisplay(segments)
+    }
+
+    fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The indices of the segments that are on, a is 0.
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..7).filter(move |index| self.0 & (1 << index) != 0)
+    }
+}
+
+impl fmt::Display for SegmentDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for index in self.indices() {
+            write!(f, "{}", (b'a' + index as u8) as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// The segments that are on for each digit.
+const DIGITS: [(char, SegmentDisplay); 10] = [
+    ('0', SegmentDisplay::from_letters("abcefg")),
+    ('1', SegmentDisplay::from_letters("cf")),
+    ('2', SegmentDisplay::from_letters("acdeg")),
+    ('3', SegmentDisplay::from_letters("acdfg")),
+    ('4', SegmentDisplay::from_letters("bcdf")),
+    ('5', SegmentDisplay::from_letters("abdfg")),
+    ('6', SegmentDisplay::from_letters("abdefg")),
+    ('7', SegmentDisplay::from_letters("acf")),
+    ('8', SegmentDisplay::from_letters("abcdefg")),
+    ('9', SegmentDisplay::from_letters("abcdfg")),
+];
+/// The segments of the letters that come after the digits in hexadecimal.
+const HEX_LETTERS: [(char, SegmentDisplay); 6] = [
+    ('A', SegmentDisplay::from_letters("abcdef")),
+    ('b', SegmentDisplay::from_letters("bdefg")),
+    ('C', SegmentDisplay::from_letters("abeg")),
+    ('d', SegmentDisplay::from_letters("cdefg")),
+    ('E', SegmentDisplay::from_letters("abdeg")),
+    ('F', SegmentDisplay::from_letters("abde")),
+];
+const ALL_SEGMENTS: u8 = 0b111_1111;
+
+/// The symbols a display can show and the segments that are on for each.
+#[derive(Debug, Clone, PartialEq)]
+struct Font {
+    symbols: Vec<(char, SegmentDisplay)>,
+}
+
+impl Font {
+    fn standard() -> Font {
+        Font {
+            symbols: DIGITS.to_vec(),
+        }
+    }
+
+    fn hex() -> Font {
+        Font {
+            symbols: DIGITS.iter().chain(&HEX_LETTERS).copied().collect(),
+        }
+    }
+
+    /// Parse a font as standard, hex or a comma separated list of symbols
+    /// with their segments, e.g. 1=cf,7=acf. None when a symbol or its
+    /// segments appear more than once.
+    fn parse(description: &str) -> Option<Font> {
+        match description {
+            "standard" => return Some(Font::standard()),
+            "hex" => return Some(Font::hex()),
+            _ => {}
+        }
+        let mut symbols: Vec<(char, SegmentDisplay)> = vec![];
+        for symbol in description.split(',') {
+            let (symbol, segments) = symbol.split_once('=')?;
+            let symbol = symbol.chars().exactly_one().ok()?;
+            let segments = SegmentDisplay::parse(segments).ok()?;
+            if symbols
                 .iter()
-                .map(|display| (identify_digit(&vec![], display), display))
+                .any(|(other, other_segments)| *other == symbol || *other_segments == segments)
+            {
+                return None;
+            }
+            symbols.push((symbol, segments));
+        }
+        Some(Font { symbols })
+    }
+
+    fn symbol(&self, segments: &SegmentDisplay) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|(_, symbol_segments)| symbol_segments == segments)
+            .map(|(symbol, _)| *symbol)
+    }
+
+    /// The radix of the values the font shows, when its symbols are the
+    /// digits of one in order.
+    fn radix(&self) -> Option<u32> {
+        let radix = self.symbols.len() as u32;
+        self.symbols
+            .iter()
+            .enumerate()
+            .all(|(value, (symbol, _))| symbol.to_digit(radix) == Some(value as u32))
+            .then_some(radix)
+    }
+}
+
+/// The index of the segment every wire, a to g, is connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Wiring([u8; 7]);
+
+impl Wiring {
+    /// The segments that light up for the wires.
+    fn segments(&self, display: &SegmentDisplay) -> SegmentDisplay {
+        SegmentDisplay(
+            display
+                .indices()
+                .fold(0, |segments, wire| segments | 1 << self.0[wire]),
+        )
+    }
+
+    fn decode(&self, display: &SegmentDisplay, font: &Font) -> Option<char> {
+        font.symbol(&self.segments(display))
+    }
+}
+
+impl fmt::Display for Wiring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in self.0 {
+            write!(f, "{}", (b'a' + segment) as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// The segments each wire could still be connected to, a bit for each.
+type Candidates = [u8; 7];
+
+/// Find every wiring that turns each pattern into a different symbol of the
+/// font. The segments a wire can connect to are narrowed down by the
+/// patterns first: a wire in a pattern has to be a segment of a symbol with
+/// as many segments, a wire not in it can't be a segment all those symbols
+/// share. Then the wire with the fewest options left is tried with each of
+/// them, narrowing down the others again, until every wire is connected.
+fn solve_wiring(patterns: &[SegmentDisplay], font: &Font) -> Vec<Wiring> {
+    // a repeated pattern tells nothing new, but would need a symbol of its own
+    let patterns: Vec<SegmentDisplay> = patterns.iter().copied().unique().collect();
+    let mut candidates: Candidates = [ALL_SEGMENTS; 7];
+    for pattern in &patterns {
+        let symbols = font
+            .symbols
+            .iter()
+            .map(|(_, segments)| segments)
+            .filter(|segments| segments.len() == pattern.len());
+        let lit = symbols.clone().fold(0, |lit, segments| lit | segments.0);
+        let always_lit = symbols.fold(ALL_SEGMENTS, |lit, segments| lit & segments.0);
+        for (wire, wire_candidates) in candidates.iter_mut().enumerate() {
+            if pattern.0 & (1 << wire) != 0 {
+                *wire_candidates &= lit;
+            } else {
+                *wire_candidates &= !always_lit;
+            }
+        }
+    }
+
+    let mut wirings = vec![];
+    search_wiring(candidates, &patterns, font, &mut wirings);
+    wirings
+}
+
+fn search_wiring(
+    mut candidates: Candidates,
+    patterns: &[SegmentDisplay],
+    font: &Font,
+    wirings: &mut Vec<Wiring>,
+) {
+    if !propagate(&mut candidates) {
+        return;
+    }
+
+    // try the options of the wire with the fewest
+    let undecided = (0..7)
+        .filter(|&wire| candidates[wire].count_ones() > 1)
+        .min_by_key(|&wire| candidates[wire].count_ones());
+    match undecided {
+        Some(wire) => {
+            for segment in SegmentDisplay(candidates[wire]).indices() {
+                let mut guess = candidates;
+                guess[wire] = 1 << segment;
+                search_wiring(guess, patterns, font, wirings);
+            }
+        }
+        None => {
+            let wiring = Wiring(candidates.map(|segment| segment.trailing_zeros() as u8));
+            let symbols: Option<HashSet<char>> = patterns
+                .iter()
+                .map(|pattern| wiring.decode(pattern, font))
                 .collect();
+            if symbols.is_some_and(|symbols| symbols.len() == patterns.len()) {
+                wirings.push(wiring);
+            }
+        }
+    }
+}
 
-            // look if digit 1 completely overlaps
-            identified_digits.get(&Some(1)).and_then(|digit_1| {
-                match (
-                    digit_1.chars().all(|section| display.contains(section)),
-                    length,
-                ) {
-                    (true, 5) => Some(3), // with a length 5 and overlap with digit 1 it's a 3
-                    (true, 6) => {
-                        // digit 1 overlaps and 6 sections are on, it's a 0 or 9
-                        identified_digits.get(&Some(4)).and_then(|digit_4| {
-                            if digit_4.chars().all(|section| display.contains(section)) {
-                                // if digit 4 fully overlaps, it's a 9
-                                Some(9)
-                            } else {
-                                // if digit 4 doesn't fully overlap, it's a 0
-                                Some(0)
-                            }
-                        })
-                    }
-                    (false, 5) => {
-                        // digit 1 doesn't overlaps and 5 sections are on, it's a 2 or 5
-                        identified_digits.get(&Some(4)).and_then(|digit_4| {
-                            match digit_4
-                                .chars()
-                                .filter(|&section| display.contains(section))
-                                .count()
-                            {
-                                2 => Some(2), // 2 sections of digit 4 overlap, it's a 2
-                                3 => Some(5), // 3 sections of digit 4 overlap, it's a 5
-                                _ => None,
-                            }
-                        })
+/// Remove the segments of wires that are decided from the other wires,
+/// false when a wire has nothing left to connect to.
+fn propagate(candidates: &mut Candidates) -> bool {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for wire in 0..7 {
+            match candidates[wire].count_ones() {
+                0 => return false,
+                1 => {
+                    let segment = candidates[wire];
+                    for (other, other_candidates) in candidates.iter_mut().enumerate() {
+                        if other != wire && *other_candidates & segment != 0 {
+                            *other_candidates &= !segment;
+                            changed = true;
+                        }
                     }
-                    (false, 6) => Some(6), // with a length 6 and no overlap of digit 1, it's a 6
-                    _ => None,
                 }
+                _ => {}
+            }
+        }
+    }
+    true
+}
+
+/// What could and couldn't be worked out about an entry.
+#[derive(Debug, PartialEq)]
+struct Diagnosis {
+    /// the wirings that fit the patterns
+    wirings: Vec<Wiring>,
+    /// the symbols none of the patterns are with any of the wirings
+    missing_symbols: Vec<char>,
+    /// the symbols each output value is with any of the wirings
+    possible_symbols: Vec<Vec<char>>,
+}
+
+fn diagnose(patterns: &[SegmentDisplay], displays: &[SegmentDisplay], font: &Font) -> Diagnosis {
+    let wirings = solve_wiring(patterns, font);
+    // the symbols of the font the displays could be, in the order of the font
+    let possible = |displays: &[SegmentDisplay]| -> Vec<char> {
+        let decoded: HashSet<char> = wirings
+            .iter()
+            .cartesian_product(displays)
+            .filter_map(|(wiring, display)| wiring.decode(display, font))
+            .collect();
+        font.symbols
+            .iter()
+            .map(|(symbol, _)| *symbol)
+            .filter(|symbol| decoded.contains(symbol))
+            .collect()
+    };
+    let missing_symbols = if wirings.is_empty() {
+        vec![]
+    } else {
+        let seen = possible(patterns);
+        font.symbols
+            .iter()
+            .map(|(symbol, _)| *symbol)
+            .filter(|symbol| !seen.contains(symbol))
+            .collect()
+    };
+    let possible_symbols = displays
+        .iter()
+        .map(|display| possible(std::slice::from_ref(display)))
+        .collect();
+    Diagnosis {
+        wirings,
+        missing_symbols,
+        possible_symbols,
+    }
+}
+
+impl Diagnosis {
+    /// The output value, when every symbol of it is certain.
+    fn value(&self) -> Option<String> {
+        self.possible_symbols
+            .iter()
+            .map(|symbols| match symbols[..] {
+                [symbol] => Some(symbol),
+                _ => None,
             })
+            .collect()
+    }
+}
+
+/// The most wirings to list in a diagnosis.
+const DIAGNOSIS_WIRINGS: usize = 5;
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.wirings.len() {
+            0 => writeln!(f, "  no wiring fits the patterns")?,
+            1 => writeln!(f, "  only wiring {} fits the patterns", self.wirings[0])?,
+            amount => writeln!(
+                f,
+                "  {} wirings fit the patterns: {}{}",
+                amount,
+                self.wirings.iter().take(DIAGNOSIS_WIRINGS).join(", "),
+                if amount > DIAGNOSIS_WIRINGS {
+                    ", .."
+                } else {
+                    ""
+                }
+            )?,
+        }
+        if !self.missing_symbols.is_empty() {
+            writeln!(
+                f,
+                "  there are no patterns for symbols {}",
+                self.missing_symbols.iter().join(", ")
+            )?;
         }
+        for (position, symbols) in self.possible_symbols.iter().enumerate() {
+            match symbols.len() {
+                0 => writeln!(f, "  output value {} isn't any symbol", position + 1)?,
+                1 => {}
+                _ => writeln!(
+                    f,
+                    "  output value {} could be any of {}",
+                    position + 1,
+                    symbols.iter().join(", ")
+                )?,
+            }
+        }
+        Ok(())
     }
 }
 
-fn load_segment_displays(input: &str) -> Vec<(SegmentDisplays, SegmentDisplays)> {
+/// Render a display the way the puzzle draws them, with the letter of each
+/// segment that is on and dots for the ones that are off.
+fn render_display(display: &SegmentDisplay) -> [String; 7] {
+    let segment = |index: usize, width: usize| {
+        let letter = if display.0 & (1 << index) != 0 {
+            (b'a' + index as u8) as char
+        } else {
+            '.'
+        };
+        letter.to_string().repeat(width)
+    };
+    let across = |index| format!(" {} ", segment(index, 4));
+    let down = |left, right| format!("{}    {}", segment(left, 1), segment(right, 1));
+    [
+        across(0),
+        down(1, 2),
+        down(1, 2),
+        across(3),
+        down(4, 5),
+        down(4, 5),
+        across(6),
+    ]
+}
+
+/// Render the output values of an entry side by side, as the scrambled
+/// wires turn on the segments and as the wiring corrects them.
+fn render_entry(displays: &[SegmentDisplay], wiring: &Wiring, font: &Font) -> String {
+    let render_row = |displays: &mut dyn Iterator<Item = SegmentDisplay>| -> Vec<String> {
+        let rendered: Vec<[String; 7]> = displays.map(|display| render_display(&display)).collect();
+        (0..7)
+            .map(|line| rendered.iter().map(|lines| &lines[line]).join("  "))
+            .collect()
+    };
+    let scrambled = render_row(&mut displays.iter().copied());
+    let corrected = render_row(&mut displays.iter().map(|display| wiring.segments(display)));
+    let value: String = displays
+        .iter()
+        .map(|display| wiring.decode(display, font).unwrap_or('?'))
+        .collect();
+
+    let width = scrambled[0].len();
+    let mut rendered = format!(
+        "{:width$}    wiring {} shows {}\n",
+        "scrambled", wiring, value
+    );
+    for (scrambled, corrected) in scrambled.iter().zip(&corrected) {
+        rendered += format!("{}    {}", scrambled, corrected).trim_end();
+        rendered += "\n";
+    }
+    rendered
+}
+
+/// Load the patterns and output values of every entry, on failure the index
+/// of the first invalid line is returned with the reason.
+fn load_segment_displays(
+    input: &str,
+) -> Result<Vec<(SegmentDisplays, SegmentDisplays)>, (usize, SegmentError)> {
+    let parse = |displays: &str| {
+        displays
+            .split_whitespace()
+            .map(SegmentDisplay::parse)
+            .collect::<Result<SegmentDisplays, _>>()
+    };
     input
         .lines()
-        .filter_map(|line| {
+        .enumerate()
+        .filter_map(|(index, line)| {
             line.split('|')
                 .tuples()
                 .next()
-                .map(|(examples, display): (&str, &str)| {
-                    (
-                        examples.split_whitespace().collect(),
-                        display.split_whitespace().collect(),
-                    )
+                .map(|(examples, displays): (&str, &str)| {
+                    parse(examples)
+                        .and_then(|examples| Ok((examples, parse(displays)?)))
+                        .map_err(|error| (index, error))
                 })
         })
         .collect()
@@ -288,58 +767,630 @@ fn load_segment_displays(input: &str) -> Vec<(SegmentDisplays, SegmentDisplays)>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test::Bencher;
     use test_case::test_case;
 
+    fn displays(letters: &[&str]) -> SegmentDisplays {
+        letters
+            .iter()
+            .map(|letters| SegmentDisplay::parse(letters).unwrap())
+            .collect()
+    }
+
     #[test]
     fn test_load_segment_displays() {
         let input = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n";
 
         let expected_segment_displays = vec![(
-            vec![
+            displays(&[
                 "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb",
                 "ab",
-            ],
-            vec!["cdfeb", "fcadb", "cdfeb", "cdbaf"],
+            ]),
+            displays(&["cdfeb", "fcadb", "cdfeb", "cdbaf"]),
         )];
 
-        assert_eq!(load_segment_displays(input), expected_segment_displays);
-    }
-
-    #[test_case("cagedb" => Some(0) ; "cagedb, 0")]
-    #[test_case("ab" => Some(1) ; "ab, 1")]
-    #[test_case("gcdfa" => Some(2) ; "gcdfa, 2")]
-    #[test_case("fbcad" => Some(3) ; "fbcad, 3")]
-    #[test_case("eafb" => Some(4) ; "eafb, 4")]
-    #[test_case("cdfbe" => Some(5) ; "cdfbe, 5")]
-    #[test_case("cdfgeb" => Some(6) ; "cdfgeb, 6")]
-    #[test_case("dab" => Some(7) ; "dab, 7")]
-    #[test_case("acedgfb" => Some(8) ; "acedgfb, 8")]
-    #[test_case("cefabd" => Some(9) ; "cefabd, 9")]
-    #[test_case("fcadb" => Some(3) ; "fcadb, 3")]
-    #[test_case("cdfeb" => Some(5) ; "cdfeb, 5")]
-    #[test_case("cdbaf" => Some(3) ; "cdbaf, 3")]
-    fn test_identify_digit(display: SegmentDisplay) -> Option<usize> {
-        let examples = vec![
+        assert_eq!(load_segment_displays(input), Ok(expected_segment_displays));
+    }
+
+    #[test]
+    fn test_load_segment_displays_invalid() {
+        let input = "ab abc | ab\nab abh | ab\n";
+
+        assert_eq!(
+            load_segment_displays(input),
+            Err((1, SegmentError::InvalidLetter('h')))
+        );
+    }
+
+    #[test_case("abc" => Ok(SegmentDisplay(0b111)) ; "in order")]
+    #[test_case("gfedcba" => Ok(SegmentDisplay(0b111_1111)) ; "reversed")]
+    #[test_case("cab" => Ok(SegmentDisplay::from_letters("abc")) ; "any order")]
+    #[test_case("" => Ok(SegmentDisplay(0)) ; "nothing on")]
+    #[test_case("abx" => Err(SegmentError::InvalidLetter('x')) ; "invalid letter")]
+    #[test_case("aB" => Err(SegmentError::InvalidLetter('B')) ; "upper case")]
+    #[test_case("aba" => Err(SegmentError::RepeatedLetter('a')) ; "repeated letter")]
+    fn test_segment_display_parse(letters: &str) -> Result<SegmentDisplay, SegmentError> {
+        SegmentDisplay::parse(letters)
+    }
+
+    #[test]
+    fn test_segment_display() {
+        let four = SegmentDisplay::parse("fbec").unwrap();
+
+        assert_eq!(four.to_string(), "bcef");
+        assert_eq!(four.len(), 4);
+        assert_eq!(four.indices().collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+    }
+
+    #[test_case("cagedb" => Some('0') ; "cagedb, 0")]
+    #[test_case("ab" => Some('1') ; "ab, 1")]
+    #[test_case("gcdfa" => Some('2') ; "gcdfa, 2")]
+    #[test_case("fbcad" => Some('3') ; "fbcad, 3")]
+    #[test_case("eafb" => Some('4') ; "eafb, 4")]
+    #[test_case("cdfbe" => Some('5') ; "cdfbe, 5")]
+    #[test_case("cdfgeb" => Some('6') ; "cdfgeb, 6")]
+    #[test_case("dab" => Some('7') ; "dab, 7")]
+    #[test_case("acedgfb" => Some('8') ; "acedgfb, 8")]
+    #[test_case("cefabd" => Some('9') ; "cefabd, 9")]
+    #[test_case("fcadb" => Some('3') ; "fcadb, 3")]
+    #[test_case("cdfeb" => Some('5') ; "cdfeb, 5")]
+    #[test_case("cdbaf" => Some('3') ; "cdbaf, 3")]
+    fn test_identify_digit(display: &str) -> Option<char> {
+        let examples = displays(&[
             "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
-        ];
-        identify_digit(&examples, &display)
+        ]);
+        let font = Font::standard();
+        solve_wiring(&examples, &font)[0].decode(&SegmentDisplay::parse(display).unwrap(), &font)
+    }
+
+    #[test]
+    fn test_solve_wiring() {
+        let examples = displays(&[
+            "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
+        ]);
+
+        //  dddd
+        // e    a
+        // e    a
+        //  ffff
+        // g    b
+        // g    b
+        //  cccc
+        let expected_wiring = Wiring([2, 5, 6, 0, 1, 3, 4]);
+
+        let wirings = solve_wiring(&examples, &Font::standard());
+        assert_eq!(wirings, vec![expected_wiring]);
+        assert_eq!(wirings[0].to_string(), "cfgabde");
+    }
+
+    #[test]
+    fn test_solve_wiring_repeated_pattern() {
+        // the 1 is seen twice, once with its wires the other way around
+        let examples = displays(&[
+            "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb",
+            "ab", "ba",
+        ]);
+
+        assert_eq!(
+            solve_wiring(&examples, &Font::standard()),
+            vec![Wiring([2, 5, 6, 0, 1, 3, 4])]
+        );
+    }
+
+    #[test]
+    fn test_solve_wiring_too_few_patterns() {
+        // with only a 1 the two wires can go either way, the other five
+        // can be any of the remaining segments
+        let one = displays(&["ab"]);
+        let font = Font::standard();
+        let wirings = solve_wiring(&one, &font);
+
+        assert_eq!(wirings.len(), 2 * 5 * 4 * 3 * 2);
+        assert!(wirings
+            .iter()
+            .all(|wiring| wiring.decode(&one[0], &font) == Some('1')));
+    }
+
+    #[test]
+    fn test_solve_wiring_impossible() {
+        // two patterns of 1 that don't share wires
+        assert_eq!(
+            solve_wiring(&displays(&["ab", "cd"]), &Font::standard()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_diagnose_larger_example() {
+        let input = "\
+            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce\n";
+
+        let numbers: Vec<usize> = load_segment_displays(input)
+            .unwrap()
+            .iter()
+            .map(|(examples, displays)| {
+                let diagnosis = diagnose(examples, displays, &Font::standard());
+                assert_eq!(diagnosis.wirings.len(), 1);
+                diagnosis.value().unwrap().parse::<usize>().unwrap()
+            })
+            .collect();
+
+        assert_eq!(
+            numbers,
+            vec![8394, 9781, 1197, 9361, 4873, 8418, 4548, 1625, 8717, 4315]
+        );
+        assert_eq!(numbers.iter().sum::<usize>(), 61229);
     }
 
     #[test]
     fn test_count_occurances() {
         let segment_displays = vec![
-            (vec![], vec!["fdgacbe", "cefdb", "cefbgd", "gcbe"]),
-            (vec![], vec!["fcgedb", "cgb", "dgebacf", "gc"]),
-            (vec![], vec!["cg", "cg", "fdcagb", "cbg"]),
-            (vec![], vec!["efabcd", "cedba", "gadfec", "cb"]),
-            (vec![], vec!["gecf", "egdcabf", "bgf", "bfgea"]),
-            (vec![], vec!["gebdcfa", "ecba", "ca", "fadegcb"]),
-            (vec![], vec!["cefg", "dcbef", "fcge", "gbcadfe"]),
-            (vec![], vec!["ed", "bcgafe", "cdgba", "cbgef"]),
-            (vec![], vec!["gbdfcae", "bgc", "cg", "cgb"]),
-            (vec![], vec!["fgae", "cfgab", "fg", "bagce"]),
+            (vec![], displays(&["fdgacbe", "cefdb", "cefbgd", "gcbe"])),
+            (vec![], displays(&["fcgedb", "cgb", "dgebacf", "gc"])),
+            (vec![], displays(&["cg", "cg", "fdcagb", "cbg"])),
+            (vec![], displays(&["efabcd", "cedba", "gadfec", "cb"])),
+            (vec![], displays(&["gecf", "egdcabf", "bgf", "bfgea"])),
+            (vec![], displays(&["gebdcfa", "ecba", "ca", "fadegcb"])),
+            (vec![], displays(&["cefg", "dcbef", "fcge", "gbcadfe"])),
+            (vec![], displays(&["ed", "bcgafe", "cdgba", "cbgef"])),
+            (vec![], displays(&["gbdfcae", "bgc", "cg", "cgb"])),
+            (vec![], displays(&["fgae", "cfgab", "fg", "bagce"])),
         ];
 
-        assert_eq!(count_occurance(&segment_displays, vec![1, 4, 7, 8]), 26);
+        assert_eq!(
+            count_occurance(&segment_displays, &Font::standard(), &['1', '4', '7', '8']),
+            26
+        );
+    }
+
+    #[test_case(&['1', '4', '7', '8'] => 26 ; "unique lengths")]
+    #[test_case(&['2', '3'] => 5 ; "shared lengths")]
+    #[test_case(&['1', '5'] => 11 ; "mixed")]
+    #[test_case(&['A'] => 0 ; "not in the font")]
+    fn test_count_occurances_solving_wiring(symbols: &[char]) -> usize {
+        let input = "\
+            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce\n";
+
+        count_occurance(
+            &load_segment_displays(input).unwrap(),
+            &Font::standard(),
+            symbols,
+        )
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let patterns = displays(&[
+            "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
+        ]);
+        let outputs = displays(&["cdfeb", "fcadb", "cdfeb", "cdbaf"]);
+
+        let diagnosis = diagnose(&patterns, &outputs, &Font::standard());
+
+        assert_eq!(diagnosis.wirings, vec![Wiring([2, 5, 6, 0, 1, 3, 4])]);
+        assert_eq!(diagnosis.missing_symbols, Vec::<char>::new());
+        assert_eq!(
+            diagnosis.possible_symbols,
+            vec![vec!['5'], vec!['3'], vec!['5'], vec!['3']]
+        );
+        assert_eq!(diagnosis.value(), Some("5353".to_string()));
+        assert_eq!(
+            diagnosis.to_string(),
+            "  only wiring cfgabde fits the patterns\n"
+        );
+    }
+
+    #[test_case("cf" => [" .... ", ".    c", ".    c", " .... ", ".    f", ".    f", " .... "] ; "one")]
+    #[test_case("abdfg" => [" aaaa ", "b    .", "b    .", " dddd ", ".    f", ".    f", " gggg "] ; "five")]
+    #[test_case("" => [" .... ", ".    .", ".    .", " .... ", ".    .", ".    .", " .... "] ; "blank")]
+    fn test_render_display(display: &str) -> [String; 7] {
+        render_display(&SegmentDisplay::parse(display).unwrap())
+    }
+
+    #[test]
+    fn test_render_entry() {
+        let outputs = displays(&["cdfeb", "ab"]);
+
+        let rendered = render_entry(&outputs, &Wiring([2, 5, 6, 0, 1, 3, 4]), &Font::standard());
+
+        assert_eq!(
+            rendered,
+            "\
+scrambled         wiring cfgabde shows 51
+ ....    aaaa      aaaa    ....
+b    c  b    .    b    .  .    c
+b    c  b    .    b    .  .    c
+ dddd    ....      dddd    ....
+e    f  .    .    .    f  .    f
+e    f  .    .    .    f  .    f
+ ....    ....      gggg    ....
+"
+        );
+    }
+
+    #[test]
+    fn test_diagnose_missing_patterns() {
+        // only the digits with a unique amount of segments
+        let patterns = displays(&["acedgfb", "dab", "eafb", "ab"]);
+        let outputs = displays(&["ab", "cdfeb", "eafb", "fcadb"]);
+
+        let diagnosis = diagnose(&patterns, &outputs, &Font::standard());
+
+        // the wiring isn't certain, but the output value is
+        assert_eq!(diagnosis.wirings.len(), 8);
+        assert_eq!(
+            diagnosis.missing_symbols,
+            vec!['0', '2', '3', '5', '6', '9']
+        );
+        assert_eq!(diagnosis.value(), Some("1543".to_string()));
+
+        let report = diagnosis.to_string();
+        assert!(report.starts_with("  8 wirings fit the patterns: cfeabdg, cfeadbg, cfgabde,"));
+        assert!(report.ends_with(", ..\n  there are no patterns for symbols 0, 2, 3, 5, 6, 9\n"));
+    }
+
+    #[test]
+    fn test_diagnose_ambiguous() {
+        // only the digit 1
+        let patterns = displays(&["ab"]);
+        let outputs = displays(&["ab", "cdfeb"]);
+
+        let diagnosis = diagnose(&patterns, &outputs, &Font::standard());
+
+        assert_eq!(diagnosis.possible_symbols[0], vec!['1']);
+        // the real digit is still among the possible ones
+        assert!(diagnosis.possible_symbols[1].len() > 1);
+        assert!(diagnosis.possible_symbols[1].contains(&'5'));
+        assert_eq!(diagnosis.value(), None);
+
+        let report = diagnosis.to_string();
+        assert!(report.contains("output value 2 could be any of"));
+        assert!(!report.contains("output value 1 "));
+    }
+
+    #[test]
+    fn test_diagnose_impossible() {
+        // two patterns of 1 that don't share wires
+        let patterns = displays(&["ab", "cd"]);
+        let outputs = displays(&["ab"]);
+
+        let diagnosis = diagnose(&patterns, &outputs, &Font::standard());
+
+        assert_eq!(diagnosis.value(), None);
+        assert_eq!(
+            diagnosis.to_string(),
+            "  no wiring fits the patterns\n  output value 1 isn't any symbol\n"
+        );
+    }
+
+    /// The patterns a display with the wiring shows for the segments.
+    fn scramble(wiring: &Wiring, segments: &SegmentDisplay) -> SegmentDisplay {
+        SegmentDisplay(
+            (0..7)
+                .filter(|&wire| segments.0 & (1 << wiring.0[wire]) != 0)
+                .fold(0, |wires, wire| wires | 1 << wire),
+        )
+    }
+
+    fn font(symbols: &[(char, &str)]) -> Font {
+        Font {
+            symbols: symbols
+                .iter()
+                .map(|&(symbol, segments)| (symbol, SegmentDisplay::from_letters(segments)))
+                .collect(),
+        }
+    }
+
+    #[test_case("standard" => Some(Font::standard()) ; "standard")]
+    #[test_case("hex" => Some(Font::hex()) ; "hex")]
+    #[test_case("1=cf,7=acf" => Some(font(&[('1', "cf"), ('7', "acf")])) ; "custom")]
+    #[test_case("1=cf,7=fc" => None ; "repeated segments")]
+    #[test_case("1=cf,1=acf" => None ; "repeated symbol")]
+    #[test_case("1=cx" => None ; "invalid segment")]
+    #[test_case("10=cf" => None ; "symbol too long")]
+    #[test_case("1" => None ; "no segments")]
+    fn test_font_parse(description: &str) -> Option<Font> {
+        Font::parse(description)
+    }
+
+    #[test]
+    fn test_font_radix() {
+        assert_eq!(Font::standard().radix(), Some(10));
+        assert_eq!(Font::hex().radix(), Some(16));
+        assert_eq!(Font::parse("0=abcefg,1=cf").unwrap().radix(), Some(2));
+        assert_eq!(Font::parse("1=cf,0=abcefg").unwrap().radix(), None);
+        assert_eq!(Font::parse("L=def,H=bcdef").unwrap().radix(), None);
+    }
+
+    #[test]
+    fn test_hex_font_symbols_differ() {
+        let font = Font::hex();
+
+        assert_eq!(
+            font.symbols
+                .iter()
+                .map(|(_, segments)| segments)
+                .unique()
+                .count(),
+            16
+        );
+        assert_eq!(
+            font.symbol(&SegmentDisplay::from_letters("abde")),
+            Some('F')
+        );
+    }
+
+    #[test]
+    fn test_diagnose_hex() {
+        let font = Font::hex();
+        let wiring = Wiring([2, 5, 6, 0, 1, 3, 4]);
+        let patterns: SegmentDisplays = font
+            .symbols
+            .iter()
+            .map(|(_, segments)| scramble(&wiring, segments))
+            .collect();
+        let outputs: SegmentDisplays = ['C', '0', 'F', 'E']
+            .iter()
+            .map(|symbol| {
+                let (_, segments) = font.symbols.iter().find(|(s, _)| s == symbol).unwrap();
+                scramble(&wiring, segments)
+            })
+            .collect();
+
+        let diagnosis = diagnose(&patterns, &outputs, &font);
+
+        assert_eq!(diagnosis.wirings, vec![wiring]);
+        assert_eq!(diagnosis.value(), Some("C0FE".to_string()));
+        assert_eq!(parse_value("C0FE", font.radix().unwrap()), Some(0xC0FE));
+    }
+
+    #[test_case("5353", 10 => Some(5353) ; "decimal")]
+    #[test_case("0110", 2 => Some(6) ; "binary")]
+    #[test_case("FFFFFFFFFFFFFFFF", 16 => Some(u64::MAX) ; "largest")]
+    #[test_case("10000000000000000", 16 => None ; "too big")]
+    fn test_parse_value(value: &str, radix: u32) -> Option<u64> {
+        parse_value(value, radix)
+    }
+
+    #[test]
+    fn test_diagnose_custom_font() {
+        // a font with only the symbols for low and high
+        let font = Font::parse("L=def,H=bcdef").unwrap();
+        let wiring = Wiring([6, 5, 4, 3, 2, 1, 0]);
+        let low = scramble(&wiring, &SegmentDisplay::from_letters("def"));
+        let high = scramble(&wiring, &SegmentDisplay::from_letters("bcdef"));
+
+        let diagnosis = diagnose(&[low, high], &[high, low, low], &font);
+
+        // the wires for a and g are never on and neither pattern tells
+        // b from c or d, e and f apart
+        assert_eq!(diagnosis.wirings.len(), 2 * 2 * (3 * 2));
+        assert!(diagnosis.wirings.contains(&wiring));
+        assert_eq!(diagnosis.value(), Some("HLL".to_string()));
+    }
+
+    #[bench]
+    fn bench_solve_wiring(b: &mut Bencher) {
+        let segment_displays = load_segment_displays(INPUT).unwrap();
+
+        let font = Font::standard();
+
+        b.iter(|| {
+            segment_displays
+                .iter()
+                .map(|(examples, displays)| diagnose(examples, displays, &font).value().unwrap())
+                .map(|value| value.parse::<usize>().unwrap())
+                .sum::<usize>()
+        });
+    }
+
+    #[bench]
+    fn bench_load_segment_displays(b: &mut Bencher) {
+        b.iter(|| load_segment_displays(INPUT).unwrap().len());
+    }
+
+    /// The segments each wire can connect to, as the wiring used to be solved
+    /// with string patterns and sets of letters, to compare the masks with.
+    type LetterCandidates = [HashSet<char>; 7];
+
+    const SEGMENT_LETTERS: &str = "abcdefg";
+
+    fn solve_wiring_with_letters(patterns: &[&str]) -> Vec<[char; 7]> {
+        let digits = DIGITS.map(|(_, digit)| digit.to_string());
+        let mut candidates: LetterCandidates = Default::default();
+        for wire_candidates in candidates.iter_mut() {
+            wire_candidates.extend(SEGMENT_LETTERS.chars());
+        }
+        for pattern in patterns {
+            let digits: Vec<HashSet<char>> = digits
+                .iter()
+                .filter(|digit| digit.len() == pattern.len())
+                .map(|digit| digit.chars().collect())
+                .collect();
+            let lit: HashSet<char> = digits.iter().flatten().copied().collect();
+            let always_lit: HashSet<char> = SEGMENT_LETTERS
+                .chars()
+                .filter(|segment| digits.iter().all(|digit| digit.contains(segment)))
+                .collect();
+            for (wire, wire_candidates) in SEGMENT_LETTERS.chars().zip(candidates.iter_mut()) {
+                if pattern.contains(wire) {
+                    wire_candidates.retain(|segment| lit.contains(segment));
+                } else {
+                    wire_candidates.retain(|segment| !always_lit.contains(segment));
+                }
+            }
+        }
+
+        let mut wirings = vec![];
+        search_wiring_with_letters(candidates, patterns, &digits, &mut wirings);
+        wirings
+    }
+
+    fn search_wiring_with_letters(
+        mut candidates: LetterCandidates,
+        patterns: &[&str],
+        digits: &[String; 10],
+        wirings: &mut Vec<[char; 7]>,
+    ) {
+        if !propagate_letters(&mut candidates) {
+            return;
+        }
+        let undecided = (0..7)
+            .filter(|&wire| candidates[wire].len() > 1)
+            .min_by_key(|&wire| candidates[wire].len());
+        match undecided {
+            Some(wire) => {
+                for segment in candidates[wire].iter().copied().sorted() {
+                    let mut guess = candidates.clone();
+                    guess[wire] = [segment].into_iter().collect();
+                    search_wiring_with_letters(guess, patterns, digits, wirings);
+                }
+            }
+            None => {
+                let wiring = candidates.map(|segments| *segments.iter().next().unwrap());
+                let decode = |pattern: &&str| {
+                    let segments: String = pattern
+                        .chars()
+                        .map(|wire| wiring[SEGMENT_LETTERS.find(wire).unwrap()])
+                        .sorted()
+                        .collect();
+                    digits.iter().position(|digit| *digit == segments)
+                };
+                let decoded: Option<HashSet<usize>> = patterns.iter().map(decode).collect();
+                if decoded.is_some_and(|decoded| decoded.len() == patterns.len()) {
+                    wirings.push(wiring);
+                }
+            }
+        }
+    }
+
+    fn propagate_letters(candidates: &mut LetterCandidates) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for wire in 0..7 {
+                match candidates[wire].len() {
+                    0 => return false,
+                    1 => {
+                        let segment = *candidates[wire].iter().next().unwrap();
+                        for (other, other_candidates) in candidates.iter_mut().enumerate() {
+                            if other != wire && other_candidates.remove(&segment) {
+                                changed = true;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// The patterns of every entry in the input, as strings.
+    fn input_entry_patterns() -> Vec<Vec<&'static str>> {
+        INPUT
+            .lines()
+            .map(|line| line.split('|').next().unwrap().split_whitespace().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_solve_wiring_matches_letters() {
+        let font = Font::standard();
+
+        for patterns in input_entry_patterns() {
+            let wirings: Vec<String> = solve_wiring(&displays(&patterns), &font)
+                .iter()
+                .map(Wiring::to_string)
+                .collect();
+            let letter_wirings: Vec<String> = solve_wiring_with_letters(&patterns)
+                .iter()
+                .map(|wiring| wiring.iter().collect())
+                .collect();
+
+            assert_eq!(wirings, letter_wirings);
+        }
+    }
+
+    #[bench]
+    fn bench_solve_wiring_masks(b: &mut Bencher) {
+        let patterns: Vec<SegmentDisplays> = input_entry_patterns()
+            .iter()
+            .map(|patterns| displays(patterns))
+            .collect();
+        let font = Font::standard();
+
+        b.iter(|| {
+            patterns
+                .iter()
+                .map(|patterns| solve_wiring(patterns, &font).len())
+                .sum::<usize>()
+        });
+    }
+
+    #[bench]
+    fn bench_solve_wiring_letters(b: &mut Bencher) {
+        let patterns = input_entry_patterns();
+
+        b.iter(|| {
+            patterns
+                .iter()
+                .map(|patterns| solve_wiring_with_letters(patterns).len())
+                .sum::<usize>()
+        });
+    }
+
+    /// All patterns in the input, to compare the overlap of every pair.
+    fn input_patterns() -> Vec<&'static str> {
+        INPUT
+            .lines()
+            .flat_map(|line| line.split('|').next().unwrap().split_whitespace())
+            .take(200)
+            .collect()
+    }
+
+    #[bench]
+    fn bench_contains_strings(b: &mut Bencher) {
+        let patterns = input_patterns();
+
+        // the way the segments were compared when they were strings
+        b.iter(|| {
+            patterns
+                .iter()
+                .cartesian_product(&patterns)
+                .filter(|(display, other)| other.chars().all(|segment| display.contains(segment)))
+                .count()
+        });
+    }
+
+    #[bench]
+    fn bench_contains_masks(b: &mut Bencher) {
+        let patterns = displays(&input_patterns());
+
+        b.iter(|| {
+            patterns
+                .iter()
+                .cartesian_product(&patterns)
+                .filter(|(display, other)| display.0 & other.0 == other.0)
+                .count()
+        });
     }
 }
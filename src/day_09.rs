@@ -88,11 +88,12 @@
 ///
 /// What do you get if you multiply together the sizes of the three largest
 /// basins?
-use std::collections::{HashMap, HashSet};
+use crate::grid::{Grid, Position};
+use std::collections::HashSet;
 
 const INPUT: &str = include_str!("../input/day_09");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     let height_map = load_height_map(INPUT);
 
     let lowest_points = find_lowest_points(&height_map);
@@ -139,7 +140,10 @@ fn expand_basin(mut basin: HeightSet, height_map: &HeightMap) -> HeightSet {
         .flat_map(|(position, height)| {
             height_map
                 .neighbours(*position)
-                .filter(|(_, neighbour_height)| neighbour_height > height && neighbour_height != &9)
+                .filter(|&(_, neighbour_height)| {
+                    neighbour_height > height && *neighbour_height != 9
+                })
+                .map(|(position, height)| (position, *height))
         })
         .collect();
 
@@ -156,139 +160,40 @@ fn expand_basin(mut basin: HeightSet, height_map: &HeightMap) -> HeightSet {
 fn find_lowest_points(height_map: &HeightMap) -> HeightSet {
     height_map
         .iter()
-        .filter(|(&position, &height)| {
+        .filter(|&(position, height)| {
             height_map
                 .neighbours(position)
                 .all(|(_, neighbour_height)| height < neighbour_height)
         })
-        .map(|(position, height)| (*position, *height))
+        .map(|(position, height)| (position, *height))
         .collect()
 }
 
-type Coordinates = (i32, i32);
 type Height = u8;
-type HeightMap = HashMap<Coordinates, Height>;
-type HeightSet = HashSet<(Coordinates, Height)>;
-
-trait HeightMapNeighbours<'a> {
-    fn neighbours(&'a self, position: Coordinates) -> HeightMapNeighboursIter<'a>;
-}
-
-impl<'a> HeightMapNeighbours<'a> for HeightMap {
-    fn neighbours(&self, position: Coordinates) -> HeightMapNeighboursIter {
-        HeightMapNeighboursIter::new(self, position)
-    }
-}
-
-struct HeightMapNeighboursIter<'a> {
-    height_map: &'a HeightMap,
-    iterator: Box<dyn Iterator<Item = Coordinates>>,
-}
-impl<'a> HeightMapNeighboursIter<'a> {
-    fn new(height_map: &'a HeightMap, (x, y): Coordinates) -> HeightMapNeighboursIter<'a> {
-        HeightMapNeighboursIter {
-            height_map,
-            iterator: { Box::new([(x - 1, y), (x, y + 1), (x + 1, y), (x, y - 1)].into_iter()) },
-        }
-    }
-}
-
-impl<'a> Iterator for HeightMapNeighboursIter<'a> {
-    type Item = (Coordinates, Height);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(position) = self.iterator.next() {
-            let neighbour = self.height_map.get(&position);
-            if neighbour.is_some() {
-                return neighbour.map(|height| (position, *height));
-            }
-        }
-        None
-    }
-}
+type HeightMap = Grid<Height>;
+type HeightSet = HashSet<(Position, Height)>;
 
 fn load_height_map(input: &str) -> HeightMap {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(x, line)| {
-            line.chars()
-                .filter_map(|c| c.to_digit(10))
-                .enumerate()
-                .map(move |(y, height)| ((x as i32, y as i32), height as u8))
-        })
-        .collect()
+    Grid::parse(input, |c| c.to_digit(10).map(|height| height as Height))
+        .expect("Expected the height map to be a grid of digits")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+    use test::Bencher;
 
     fn height_map_1() -> HeightMap {
-        // 2199943210
-        // 3987894921
-        // 9856789892
-        // 8767896789
-        // 9899965678
-        [
-            // 2199943210
-            ((0, 0), 2),
-            ((0, 1), 1),
-            ((0, 2), 9),
-            ((0, 3), 9),
-            ((0, 4), 9),
-            ((0, 5), 4),
-            ((0, 6), 3),
-            ((0, 7), 2),
-            ((0, 8), 1),
-            ((0, 9), 0),
-            // 3987894921
-            ((1, 0), 3),
-            ((1, 1), 9),
-            ((1, 2), 8),
-            ((1, 3), 7),
-            ((1, 4), 8),
-            ((1, 5), 9),
-            ((1, 6), 4),
-            ((1, 7), 9),
-            ((1, 8), 2),
-            ((1, 9), 1),
-            // 9856789892
-            ((2, 0), 9),
-            ((2, 1), 8),
-            ((2, 2), 5),
-            ((2, 3), 6),
-            ((2, 4), 7),
-            ((2, 5), 8),
-            ((2, 6), 9),
-            ((2, 7), 8),
-            ((2, 8), 9),
-            ((2, 9), 2),
-            // 8767896789
-            ((3, 0), 8),
-            ((3, 1), 7),
-            ((3, 2), 6),
-            ((3, 3), 7),
-            ((3, 4), 8),
-            ((3, 5), 9),
-            ((3, 6), 6),
-            ((3, 7), 7),
-            ((3, 8), 8),
-            ((3, 9), 9),
-            // 9899965678
-            ((4, 0), 9),
-            ((4, 1), 8),
-            ((4, 2), 9),
-            ((4, 3), 9),
-            ((4, 4), 9),
-            ((4, 5), 6),
-            ((4, 6), 5),
-            ((4, 7), 6),
-            ((4, 8), 7),
-            ((4, 9), 8),
-        ]
-        .into_iter()
-        .collect()
+        #[rustfmt::skip]
+        let heights = vec![
+            2, 1, 9, 9, 9, 4, 3, 2, 1, 0,
+            3, 9, 8, 7, 8, 9, 4, 9, 2, 1,
+            9, 8, 5, 6, 7, 8, 9, 8, 9, 2,
+            8, 7, 6, 7, 8, 9, 6, 7, 8, 9,
+            9, 8, 9, 9, 9, 6, 5, 6, 7, 8,
+        ];
+        Grid::new(10, 5, heights).unwrap()
     }
 
     #[test]
@@ -309,10 +214,10 @@ mod tests {
 
         let mut neighbours = height_map.neighbours((1, 1));
 
-        assert_eq!(neighbours.next(), Some(((0, 1), 1)));
-        assert_eq!(neighbours.next(), Some(((1, 2), 8)));
-        assert_eq!(neighbours.next(), Some(((2, 1), 8)));
-        assert_eq!(neighbours.next(), Some(((1, 0), 3)));
+        assert_eq!(neighbours.next(), Some(((0, 1), &1)));
+        assert_eq!(neighbours.next(), Some(((1, 2), &8)));
+        assert_eq!(neighbours.next(), Some(((2, 1), &8)));
+        assert_eq!(neighbours.next(), Some(((1, 0), &3)));
         assert_eq!(neighbours.next(), None);
     }
 
@@ -322,8 +227,8 @@ mod tests {
 
         let mut neighbours = height_map.neighbours((4, 0));
 
-        assert_eq!(neighbours.next(), Some(((3, 0), 8)));
-        assert_eq!(neighbours.next(), Some(((4, 1), 8)));
+        assert_eq!(neighbours.next(), Some(((3, 0), &8)));
+        assert_eq!(neighbours.next(), Some(((4, 1), &8)));
         assert_eq!(neighbours.next(), None);
     }
 
@@ -352,4 +257,81 @@ mod tests {
             expected_basin_sizes
         );
     }
+
+    /// The height map as it used to be stored, to compare the grid with.
+    type HashHeightMap = HashMap<(i32, i32), Height>;
+
+    fn load_hash_height_map(input: &str) -> HashHeightMap {
+        input
+            .lines()
+            .enumerate()
+            .flat_map(|(x, line)| {
+                line.chars()
+                    .filter_map(|c| c.to_digit(10))
+                    .enumerate()
+                    .map(move |(y, height)| ((x as i32, y as i32), height as Height))
+            })
+            .collect()
+    }
+
+    fn hash_neighbours(
+        height_map: &HashHeightMap,
+        (x, y): (i32, i32),
+    ) -> Box<dyn Iterator<Item = Height> + '_> {
+        Box::new(
+            [(x - 1, y), (x, y + 1), (x + 1, y), (x, y - 1)]
+                .into_iter()
+                .filter_map(|position| height_map.get(&position).copied()),
+        )
+    }
+
+    fn find_hash_lowest_points(height_map: &HashHeightMap) -> usize {
+        height_map
+            .iter()
+            .filter(|(&position, &height)| {
+                hash_neighbours(height_map, position)
+                    .all(|neighbour_height| height < neighbour_height)
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_hash_height_map_agrees() {
+        assert_eq!(
+            find_hash_lowest_points(&load_hash_height_map(INPUT)),
+            find_lowest_points(&load_height_map(INPUT)).len()
+        );
+    }
+
+    #[bench]
+    fn bench_load_height_map(b: &mut Bencher) {
+        b.iter(|| load_height_map(INPUT));
+    }
+
+    #[bench]
+    fn bench_load_hash_height_map(b: &mut Bencher) {
+        b.iter(|| load_hash_height_map(INPUT));
+    }
+
+    #[bench]
+    fn bench_find_lowest_points(b: &mut Bencher) {
+        let height_map = load_height_map(INPUT);
+
+        b.iter(|| find_lowest_points(&height_map).len());
+    }
+
+    #[bench]
+    fn bench_find_hash_lowest_points(b: &mut Bencher) {
+        let height_map = load_hash_height_map(INPUT);
+
+        b.iter(|| find_hash_lowest_points(&height_map));
+    }
+
+    #[bench]
+    fn bench_find_basin_sizes(b: &mut Bencher) {
+        let height_map = load_height_map(INPUT);
+        let lowest_points = find_lowest_points(&height_map);
+
+        b.iter(|| find_basin_sizes(&lowest_points, &height_map));
+    }
 }
@@ -139,7 +139,7 @@ use std::collections::HashMap;
 
 const INPUT: &str = include_str!("../input/day_10");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     let navigation_subsystem = INPUT;
 
     let autocompletions = navigation_subsystem
@@ -0,0 +1,290 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A position in a grid as (row, column).
+pub type Position = (usize, usize);
+
+/// The steps to the neighbours above, right, below and left of a position.
+const NEIGHBOURS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+/// The steps to all eight neighbours of a position, clockwise from above.
+#[allow(dead_code)]
+const NEIGHBOURS_DIAGONAL: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+/// A dense grid of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of the cells given row by row. None when they don't fill the
+    /// width and height exactly, or when only one of them is 0: a grid
+    /// without cells is 0x0, so it has neither rows nor columns.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Option<Grid<T>> {
+        let size = width.checked_mul(height)?;
+        (size == cells.len() && (size > 0 || width == height)).then_some(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Parse a grid with a cell for each character of each line. None when a
+    /// character isn't a cell or the lines have different lengths.
+    pub fn parse(input: &str, cell: impl Fn(char) -> Option<T>) -> Option<Grid<T>> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let row_start = cells.len();
+            for c in line.chars() {
+                cells.push(cell(c)?);
+            }
+            let row_width = cells.len() - row_start;
+            if *width.get_or_insert(row_width) != row_width {
+                return None;
+            }
+            height += 1;
+        }
+        Grid::new(width.unwrap_or(0), height, cells)
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, (row, column): Position) -> Option<usize> {
+        (row < self.height && column < self.width).then(|| row * self.width + column)
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.offset(position).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.offset(position).map(|offset| &mut self.cells[offset])
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// All cells with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// The neighbours above, right, below and left of a position that are on
+    /// the grid.
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.steps(position, &NEIGHBOURS)
+    }
+
+    /// The neighbours around a position that are on the grid, including the
+    /// diagonal ones, clockwise from above.
+    #[allow(dead_code)]
+    pub fn neighbours_diagonal(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.steps(position, &NEIGHBOURS_DIAGONAL)
+    }
+
+    fn steps<'a>(
+        &'a self,
+        (row, column): Position,
+        steps: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (Position, &'a T)> {
+        steps.iter().filter_map(move |&(row_step, column_step)| {
+            let neighbour = (
+                row.checked_add_signed(row_step)?,
+                column.checked_add_signed(column_step)?,
+            );
+            self.get(neighbour).map(|cell| (neighbour, cell))
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks can't be empty, so a grid without columns has no rows to give
+        self.cells.chunks(self.width.max(1))
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, column: usize) -> Option<impl Iterator<Item = &T>> {
+        (column < self.width).then(|| self.cells.iter().skip(column).step_by(self.width))
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.cells.iter().skip(column).step_by(self.width))
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is outside of the {}x{} grid",
+                position, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is outside of the {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn example_grid() -> Grid<u32> {
+        // 123
+        // 456
+        Grid::new(3, 2, vec![1, 2, 3, 4, 5, 6]).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(example_grid().width(), 3);
+        assert_eq!(example_grid().height(), 2);
+        assert_eq!(Grid::new(2, 2, vec![1, 2, 3]), None);
+        assert_eq!(Grid::new(usize::MAX, 2, vec![1]), None);
+        assert_eq!(Grid::new(0, 2, Vec::<u32>::new()), None);
+        assert_eq!(Grid::new(2, 0, Vec::<u32>::new()), None);
+    }
+
+    #[test_case("123\n456\n" => Some(example_grid()) ; "grid")]
+    #[test_case("123\n45\n" => None ; "uneven rows")]
+    #[test_case("123\n4x6\n" => None ; "invalid cell")]
+    #[test_case("" => Some(Grid::new(0, 0, vec![]).unwrap()) ; "empty")]
+    fn test_parse(input: &str) -> Option<Grid<u32>> {
+        Grid::parse(input, |c| c.to_digit(10))
+    }
+
+    #[test_case((0, 0) => Some(1) ; "first")]
+    #[test_case((1, 2) => Some(6) ; "last")]
+    #[test_case((2, 0) => None ; "below")]
+    #[test_case((0, 3) => None ; "right")]
+    fn test_get(position: Position) -> Option<u32> {
+        example_grid().get(position).copied()
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut grid = example_grid();
+
+        grid[(1, 1)] = 0;
+
+        assert_eq!(grid[(1, 1)], 0);
+        assert_eq!(grid.to_string(), "123\n406\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Position (0, 3) is outside of the 3x2 grid")]
+    fn test_index_out_of_bounds() {
+        let _ = example_grid()[(0, 3)];
+    }
+
+    #[test_case((0, 0) => vec![((0, 1), 2), ((1, 0), 4)] ; "corner")]
+    #[test_case((1, 1) => vec![((0, 1), 2), ((1, 2), 6), ((1, 0), 4)] ; "edge")]
+    fn test_neighbours(position: Position) -> Vec<(Position, u32)> {
+        example_grid()
+            .neighbours(position)
+            .map(|(position, cell)| (position, *cell))
+            .collect()
+    }
+
+    #[test_case((0, 0) => vec![((0, 1), 2), ((1, 1), 5), ((1, 0), 4)] ; "corner")]
+    #[test_case((1, 1) => vec![((0, 1), 2), ((0, 2), 3), ((1, 2), 6), ((1, 0), 4), ((0, 0), 1)] ; "edge")]
+    fn test_neighbours_diagonal(position: Position) -> Vec<(Position, u32)> {
+        example_grid()
+            .neighbours_diagonal(position)
+            .map(|(position, cell)| (position, *cell))
+            .collect()
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = example_grid();
+
+        assert_eq!(grid.row(1), Some(&[4, 5, 6][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(
+            grid.column(2)
+                .map(|column| column.copied().collect::<Vec<_>>()),
+            Some(vec![3, 6])
+        );
+        assert!(grid.column(3).is_none());
+        assert_eq!(
+            grid.columns()
+                .map(|column| column.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            [[1, 4], [2, 5], [3, 6]]
+        );
+    }
+
+    #[test]
+    fn test_iter() {
+        assert_eq!(
+            example_grid()
+                .iter()
+                .map(|(position, cell)| (position, *cell))
+                .collect::<Vec<_>>(),
+            [
+                ((0, 0), 1),
+                ((0, 1), 2),
+                ((0, 2), 3),
+                ((1, 0), 4),
+                ((1, 1), 5),
+                ((1, 2), 6)
+            ]
+        );
+    }
+}
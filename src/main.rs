@@ -1,7 +1,10 @@
 #![feature(option_zip)]
+#![cfg_attr(test, feature(test))]
 
 #[macro_use]
 extern crate lazy_static;
+#[cfg(test)]
+extern crate test;
 
 use std::collections::HashMap;
 use std::env;
@@ -12,11 +15,15 @@ fn main() {
 
     let modules = create_modules();
     while let Some(arg) = args.next() {
-        modules.run(&arg);
+        // options are given along with the module name, e.g. day_04:diagonals
+        let mut parts = arg.split(':');
+        let name = parts.next().unwrap();
+        let options: Vec<_> = parts.collect();
+        modules.run(name, &options);
     }
 }
 
-type Run = fn();
+type Run = fn(&[&str]);
 
 struct Modules {
     runners: HashMap<String, Run>,
@@ -27,9 +34,9 @@ impl Modules {
         self.runners.insert(name, func);
     }
 
-    fn run(&self, name: &String) {
+    fn run(&self, name: &str, options: &[&str]) {
         let runner = self.runners.get(name).unwrap();
-        runner();
+        runner(options);
     }
 }
 
@@ -45,4 +52,8 @@ macro_rules! modules {
     };
 }
 
+mod grid;
+#[cfg(test)]
+mod test_helpers;
+
 modules![day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10,];
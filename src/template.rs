@@ -4,7 +4,7 @@
 
 const INPUT: &str = include_str!("../input/day_xx");
 
-pub fn run() {
+pub fn run(_options: &[&str]) {
     println!("Not implemented yet");
     unimplemented!();
 }
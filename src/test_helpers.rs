@@ -0,0 +1,20 @@
+//! Helpers shared by the tests of the days.
+
+/// A seeded linear congruential generator, so tests can use plenty of varied
+/// but repeatable data without extra dependencies.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg(seed)
+    }
+
+    /// The next number below the bound.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as u32
+    }
+}